@@ -0,0 +1,246 @@
+//! Tools for analyzing sequences of readings
+
+use crate::{dew_point_decidegrees, Humidity, Measurement, Temperature};
+
+/// The direction that a quantity is changing
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Trend {
+    /// Increasing faster than the threshold
+    Rising,
+    /// Decreasing faster than the threshold
+    Falling,
+    /// Changing more slowly than the threshold in either direction
+    Stable,
+}
+
+/// Detects whether temperature is rising, falling, or stable over the last `N` readings
+///
+/// The detector keeps the raw values of the last `N` readings in a ring buffer. The slope is
+/// the difference between the newest and oldest readings in the buffer, divided by the number
+/// of readings between them. All calculations use raw values and integer operations.
+pub struct TrendDetector<const N: usize> {
+    /// Raw values of the most recent readings
+    samples: [u16; N],
+    /// Number of valid values in samples
+    len: usize,
+    /// Index in samples where the next reading will be stored
+    next: usize,
+    /// Minimum slope that counts as rising or falling, raw counts per reading
+    threshold: u16,
+}
+
+impl<const N: usize> TrendDetector<N> {
+    /// Creates a detector with a slope threshold in raw counts per reading
+    ///
+    /// One raw count is about 0.0027 degrees Celsius.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `N` is less than 2.
+    pub fn new(threshold_raw_per_reading: u16) -> Self {
+        assert!(N >= 2, "A trend detector needs at least 2 readings");
+        TrendDetector {
+            samples: [0; N],
+            len: 0,
+            next: 0,
+            threshold: threshold_raw_per_reading,
+        }
+    }
+
+    /// Adds a reading and returns the current trend
+    pub fn update(&mut self, temperature: &Temperature) -> Trend {
+        self.samples[self.next] = temperature.as_raw();
+        self.next = (self.next + 1) % N;
+        self.len = (self.len + 1).min(N);
+        self.trend()
+    }
+
+    /// Returns the current trend
+    ///
+    /// With fewer than 2 readings, the trend is always `Stable`.
+    pub fn trend(&self) -> Trend {
+        if self.len < 2 {
+            return Trend::Stable;
+        }
+        let newest = self.samples[(self.next + N - 1) % N];
+        let oldest = self.samples[(self.next + N - self.len) % N];
+        let change = i32::from(newest) - i32::from(oldest);
+        let limit = i32::from(self.threshold) * (self.len as i32 - 1);
+        if change > limit {
+            Trend::Rising
+        } else if change < -limit {
+            Trend::Falling
+        } else {
+            Trend::Stable
+        }
+    }
+}
+
+/// Stores the most recent temperature and humidity for calculations that need both
+///
+/// In a loop that measures temperature and humidity separately, each new measurement can be
+/// combined with the most recent measurement of the other quantity, without measuring both
+/// every time.
+#[derive(Debug, Clone, Default)]
+pub struct ClimateTracker {
+    /// Raw value of the most recent temperature
+    temperature: Option<u16>,
+    /// Raw value of the most recent humidity
+    humidity: Option<u16>,
+}
+
+impl ClimateTracker {
+    /// Creates a tracker with no stored measurements
+    pub fn new() -> Self {
+        ClimateTracker::default()
+    }
+
+    /// Stores a new temperature, replacing the previous one
+    pub fn update_temperature(&mut self, temperature: &Temperature) {
+        self.temperature = Some(temperature.as_raw());
+    }
+
+    /// Stores a new humidity, replacing the previous one
+    pub fn update_humidity(&mut self, humidity: &Humidity) {
+        self.humidity = Some(humidity.as_raw());
+    }
+
+    /// Returns the most recent temperature, if any
+    pub fn temperature(&self) -> Option<Temperature> {
+        self.temperature.map(Temperature)
+    }
+
+    /// Returns the most recent humidity, if any
+    pub fn humidity(&self) -> Option<Humidity> {
+        self.humidity.map(Humidity)
+    }
+
+    /// Returns the most recent humidity compensated for the most recent temperature,
+    /// in percent
+    ///
+    /// This returns None until both a temperature and a humidity have been stored.
+    /// See `Humidity::compensate` for details.
+    pub fn compensated_humidity(&self) -> Option<f32> {
+        let (temperature, humidity) = self.pair()?;
+        Some(humidity.compensate(&temperature))
+    }
+
+    /// Returns the dew point in tenths of a degree Celsius, calculated from the most recent
+    /// temperature and humidity
+    ///
+    /// This returns None until both a temperature and a humidity have been stored.
+    /// See `dew_point_decidegrees` for details.
+    pub fn dew_point(&self) -> Option<i16> {
+        let (temperature, humidity) = self.pair()?;
+        Some(dew_point_decidegrees(&temperature, &humidity))
+    }
+
+    /// Returns the most recent temperature and humidity, if both have been stored
+    fn pair(&self) -> Option<(Temperature, Humidity)> {
+        Some((self.temperature()?, self.humidity()?))
+    }
+}
+
+/// An on/off state with hysteresis, controlled by temperature or humidity readings
+///
+/// The state turns on when a reading is at or above the high threshold, and turns off when a
+/// reading is at or below the low threshold. Between the thresholds, the state does not
+/// change. The thresholds are raw values, so no floating-point operations are needed.
+#[derive(Debug, Clone)]
+pub struct Hysteresis {
+    /// Raw value at or below which the state turns off
+    low: u16,
+    /// Raw value at or above which the state turns on
+    high: u16,
+    /// The current state
+    state: bool,
+}
+
+impl Hysteresis {
+    /// Creates a comparator with raw thresholds, in the off state
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `low` is greater than `high`.
+    pub fn new(low: u16, high: u16) -> Self {
+        assert!(
+            low <= high,
+            "The low threshold must not be above the high threshold"
+        );
+        Hysteresis {
+            low,
+            high,
+            state: false,
+        }
+    }
+
+    /// Updates the state with a new reading and returns the state
+    pub fn update<M>(&mut self, reading: &M) -> bool
+    where
+        M: Measurement,
+    {
+        let raw = reading.raw();
+        if raw >= self.high {
+            self.state = true;
+        } else if raw <= self.low {
+            self.state = false;
+        }
+        self.state
+    }
+
+    /// Returns the current state
+    pub fn state(&self) -> bool {
+        self.state
+    }
+}
+
+#[cfg(test)]
+mod analysis_test {
+    use super::{ClimateTracker, Hysteresis, Trend, TrendDetector};
+    use crate::{dew_point_decidegrees, Humidity, Temperature};
+
+    #[test]
+    fn climate_tracker() {
+        let mut tracker = ClimateTracker::new();
+        assert!(tracker.dew_point().is_none());
+        tracker.update_temperature(&Temperature(0x6000));
+        assert!(tracker.compensated_humidity().is_none());
+        tracker.update_humidity(&Humidity(0x6838));
+        assert_eq!(
+            tracker.dew_point(),
+            Some(dew_point_decidegrees(
+                &Temperature(0x6000),
+                &Humidity(0x6838)
+            ))
+        );
+        // Replacing the temperature keeps the humidity
+        tracker.update_temperature(&Temperature(0x6800));
+        assert_eq!(
+            tracker.compensated_humidity(),
+            Some(Humidity(0x6838).compensate(&Temperature(0x6800)))
+        );
+    }
+
+    #[test]
+    fn trend() {
+        let mut detector = TrendDetector::<3>::new(10);
+        assert_eq!(detector.update(&Temperature(1000)), Trend::Stable);
+        assert_eq!(detector.update(&Temperature(1020)), Trend::Rising);
+        assert_eq!(detector.update(&Temperature(1015)), Trend::Stable);
+        // The oldest reading (1000) has been replaced
+        assert_eq!(detector.update(&Temperature(1000)), Trend::Stable);
+        assert_eq!(detector.update(&Temperature(980)), Trend::Falling);
+    }
+
+    #[test]
+    fn hysteresis() {
+        let mut hysteresis = Hysteresis::new(1000, 2000);
+        assert!(!hysteresis.update(&Temperature(1500)));
+        assert!(hysteresis.update(&Temperature(2000)));
+        assert!(hysteresis.update(&Temperature(1001)));
+        assert!(!hysteresis.update(&Temperature(1000)));
+        assert!(!hysteresis.update(&Humidity(1999)));
+        assert!(!hysteresis.state());
+    }
+}
@@ -0,0 +1,62 @@
+//! Per-unit calibration
+
+/// A piecewise-linear correction for raw temperature or humidity values
+///
+/// The table contains `N` breakpoints. Each breakpoint is a raw value and a correction in raw
+/// counts that should be added to that raw value. Between two breakpoints, the correction is
+/// interpolated linearly. Below the first breakpoint, the correction of the first breakpoint
+/// is used, and above the last breakpoint, the correction of the last breakpoint is used.
+#[derive(Debug, Clone)]
+pub struct CorrectionTable<const N: usize> {
+    /// Breakpoints (raw value, correction), sorted by raw value
+    points: [(u16, i16); N],
+}
+
+impl<const N: usize> CorrectionTable<N> {
+    /// Creates a table from breakpoints
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `N` is 0 or the raw values are not in strictly increasing
+    /// order.
+    pub fn new(points: [(u16, i16); N]) -> Self {
+        assert!(N > 0, "A correction table needs at least one breakpoint");
+        assert!(
+            points.windows(2).all(|pair| pair[0].0 < pair[1].0),
+            "Correction table breakpoints must be in increasing order"
+        );
+        CorrectionTable { points }
+    }
+
+    /// Returns the interpolated correction at a raw value, in raw counts
+    pub fn correction_at(&self, raw: u16) -> f32 {
+        let first = self.points[0];
+        if raw <= first.0 {
+            return f32::from(first.1);
+        }
+        for pair in self.points.windows(2) {
+            let (below, above) = (pair[0], pair[1]);
+            if raw <= above.0 {
+                let fraction = f32::from(raw - below.0) / f32::from(above.0 - below.0);
+                return f32::from(below.1) + fraction * (f32::from(above.1) - f32::from(below.1));
+            }
+        }
+        f32::from(self.points[N - 1].1)
+    }
+}
+
+#[cfg(test)]
+mod calibration_test {
+    use super::CorrectionTable;
+
+    #[test]
+    fn interpolation() {
+        let table = CorrectionTable::new([(0x4000, 40), (0x8000, -40), (0xc000, 0)]);
+        assert_eq!(table.correction_at(0x0000), 40.0);
+        assert_eq!(table.correction_at(0x4000), 40.0);
+        assert_eq!(table.correction_at(0x6000), 0.0);
+        assert_eq!(table.correction_at(0x7000), -20.0);
+        assert_eq!(table.correction_at(0xa000), -20.0);
+        assert_eq!(table.correction_at(0xfffc), 0.0);
+    }
+}
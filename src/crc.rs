@@ -1,25 +1,45 @@
 //! CRC for communication integrity checks
 
 /// 8-bit CRC, polynomial x^8 + x^5 + x^4 + 1, initialization 0
+///
+/// The sensor uses this CRC to protect measurement results.
 pub struct Crc {
     value: u8,
+    /// The initial value, used when resetting
+    init: u8,
 }
 
 impl Crc {
     /// Creates a new CRC calculator initialized to zero
     pub fn new() -> Self {
-        Crc { value: 0 }
+        Crc::with_init(0)
+    }
+
+    /// Creates a new CRC calculator with a non-standard initial value
+    ///
+    /// The HTU2XD sensors use an initial value of zero. Other values may be useful for
+    /// diagnosing checksum errors from sensors that do not follow the standard.
+    pub fn with_init(init: u8) -> Self {
+        Crc { value: init, init }
+    }
+
+    /// Resets this calculator to its initial value so that it can be reused
+    pub fn reset(&mut self) {
+        self.value = self.init;
     }
 
     /// Adds a byte to the CRC
+    ///
+    /// With the `crc-table` feature, this uses a 256-byte lookup table. Otherwise, it
+    /// processes the byte one bit at a time.
     pub fn add(&mut self, byte: u8) {
-        self.value ^= byte;
-        for _ in 0..8 {
-            if (self.value & 0x80) != 0 {
-                self.value = (self.value << 1) ^ 0x31;
-            } else {
-                self.value <<= 1;
-            }
+        #[cfg(feature = "crc-table")]
+        {
+            self.value = CRC_TABLE[usize::from(self.value ^ byte)];
+        }
+        #[cfg(not(feature = "crc-table"))]
+        {
+            self.value = shift_byte(self.value ^ byte);
         }
     }
     /// Adds bytes from a slice
@@ -32,11 +52,86 @@ impl Crc {
     pub fn value(&self) -> u8 {
         self.value
     }
+
+    /// Calculates the CRC of some data bytes and returns true if it is equal to `expected`
+    pub fn verify(data: &[u8], expected: u8) -> bool {
+        let mut crc = Crc::new();
+        crc.add_all(data);
+        crc.value() == expected
+    }
+}
+
+impl Default for Crc {
+    fn default() -> Self {
+        Crc::new()
+    }
+}
+
+/// Shifts all 8 bits out of a CRC remainder, applying the polynomial
+const fn shift_byte(mut value: u8) -> u8 {
+    let mut i = 0;
+    while i < 8 {
+        if (value & 0x80) != 0 {
+            value = (value << 1) ^ 0x31;
+        } else {
+            value <<= 1;
+        }
+        i += 1;
+    }
+    value
+}
+
+/// Remainders for all byte values, indexed by (current remainder XOR input byte)
+///
+/// This table takes 256 bytes of read-only memory.
+#[cfg(feature = "crc-table")]
+static CRC_TABLE: [u8; 256] = {
+    let mut table = [0u8; 256];
+    let mut i = 0;
+    while i < 256 {
+        table[i] = shift_byte(i as u8);
+        i += 1;
+    }
+    table
+};
+
+/// Checks frames that consist of any number of data bytes followed by a CRC byte
+///
+/// The CRC byte covers all the data bytes in the frame.
+pub struct CrcReader<'a> {
+    frame: &'a [u8],
+}
+
+impl<'a> CrcReader<'a> {
+    /// Creates a reader for a frame
+    pub fn new(frame: &'a [u8]) -> Self {
+        CrcReader { frame }
+    }
+
+    /// Checks the CRC byte at the end of the frame and returns the data bytes before it
+    pub fn payload(&self) -> Result<&'a [u8], CrcError> {
+        let (&expected, payload) = self.frame.split_last().ok_or(CrcError::Empty)?;
+        if Crc::verify(payload, expected) {
+            Ok(payload)
+        } else {
+            Err(CrcError::Mismatch)
+        }
+    }
+}
+
+/// An error from checking a frame with a CRC byte
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum CrcError {
+    /// The frame was empty, so it did not contain a CRC byte
+    Empty,
+    /// The CRC byte did not match the data bytes
+    Mismatch,
 }
 
 #[cfg(test)]
 mod crc_test {
-    use super::Crc;
+    use super::{Crc, CrcError, CrcReader};
     #[test]
     fn short() {
         let mut crc = Crc::new();
@@ -57,4 +152,45 @@ mod crc_test {
         crc.add_all(&[0x4e, 0x85]);
         assert_eq!(crc.value(), 0x6b);
     }
+
+    #[test]
+    fn verify() {
+        assert!(Crc::verify(&[0x68, 0x3a], 0x7c));
+        assert!(Crc::verify(&[0x4e, 0x85], 0x6b));
+        assert!(!Crc::verify(&[0x4e, 0x85], 0x6a));
+        assert!(Crc::verify(&[], 0x00));
+    }
+
+    #[test]
+    fn alternate_init() {
+        let mut crc = Crc::with_init(0xff);
+        crc.add_all(&[0x68, 0x3a]);
+        assert_eq!(crc.value(), 0xfd);
+    }
+
+    #[test]
+    fn reset() {
+        let mut crc = Crc::with_init(0xff);
+        crc.add_all(&[0x4e, 0x85]);
+        crc.reset();
+        crc.add_all(&[0x68, 0x3a]);
+        assert_eq!(crc.value(), 0xfd);
+    }
+
+    #[test]
+    #[cfg(feature = "crc-table")]
+    fn table_matches_bitwise() {
+        for i in 0..=255u8 {
+            assert_eq!(super::CRC_TABLE[usize::from(i)], super::shift_byte(i));
+        }
+    }
+
+    #[test]
+    fn reader() {
+        let frame = [0x68, 0x3a, 0x7c];
+        assert_eq!(CrcReader::new(&frame).payload(), Ok(&frame[..2]));
+        let frame = [0x68, 0x3b, 0x7c];
+        assert_eq!(CrcReader::new(&frame).payload(), Err(CrcError::Mismatch));
+        assert_eq!(CrcReader::new(&[]).payload(), Err(CrcError::Empty));
+    }
 }
@@ -0,0 +1,55 @@
+//! A common interface for environmental readings
+//!
+//! These items are only available with the `environmental` feature.
+
+use crate::{Humidity, Temperature};
+
+/// A reading from any sensor that measures temperature and relative humidity
+pub trait EnvironmentalReading {
+    /// Returns the temperature in degrees Celsius
+    fn temperature_celsius(&self) -> f32;
+    /// Returns the relative humidity in percent (0.0 = 0%, 100.0 = 100%)
+    fn relative_humidity_percent(&self) -> f32;
+}
+
+/// A temperature reading and a humidity reading taken together
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ClimateReading {
+    /// The temperature
+    pub temperature: Temperature,
+    /// The humidity
+    pub humidity: Humidity,
+}
+
+impl EnvironmentalReading for ClimateReading {
+    fn temperature_celsius(&self) -> f32 {
+        self.temperature.as_degrees_celsius()
+    }
+    fn relative_humidity_percent(&self) -> f32 {
+        self.humidity.as_percent_relative()
+    }
+}
+
+#[cfg(test)]
+mod environmental_test {
+    use super::{ClimateReading, EnvironmentalReading};
+    use crate::{Humidity, Temperature};
+
+    #[test]
+    fn climate_reading() {
+        let reading = ClimateReading {
+            temperature: Temperature(0x4e84),
+            humidity: Humidity(0x6838),
+        };
+        let reading: &dyn EnvironmentalReading = &reading;
+        assert_eq!(
+            reading.temperature_celsius(),
+            Temperature(0x4e84).as_degrees_celsius()
+        );
+        assert_eq!(
+            reading.relative_humidity_percent(),
+            Humidity(0x6838).as_percent_relative()
+        );
+    }
+}
@@ -0,0 +1,135 @@
+//! A driver for the I2C trait from embedded-hal 1.0
+//!
+//! This module requires the `hal-1` feature. The `hal-02` feature (enabled by default) is not
+//! required, so this driver can be used without depending on embedded-hal 0.2.
+
+use core::marker::PhantomData;
+use core::slice;
+
+use embedded_hal_1::i2c::I2c;
+
+use crate::{
+    parse_and_check_reading, Command, Error, Humidity, Measurement, Reading, Temperature,
+    UserRegister, ADDRESS,
+};
+
+/// HTU2XD driver for I2C buses that implement the embedded-hal 1.0 `I2c` trait
+///
+/// This driver provides the same basic functions as `crate::Htu2xd`.
+pub struct Htu2xd<I> {
+    /// The I2C address of the sensor
+    address: u8,
+    _i2c: PhantomData<I>,
+}
+
+impl<I> Htu2xd<I>
+where
+    I: I2c,
+{
+    /// Creates a driver object, but does not perform any initialization
+    ///
+    /// The driver uses the sensor's default address, 0x40.
+    pub fn new() -> Self {
+        Htu2xd::new_with_address(ADDRESS)
+    }
+
+    /// Creates a driver object that communicates with a sensor at a non-default I2C address,
+    /// but does not perform any initialization
+    pub fn new_with_address(address: u8) -> Self {
+        Htu2xd {
+            address,
+            _i2c: PhantomData,
+        }
+    }
+
+    /// Returns the I2C address that this driver uses
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
+    /// Resets the sensor and restores default settings, but does not restore the heater enable bit
+    ///
+    /// After this function returns the sensor may take up to 15 ms to reset.
+    pub fn soft_reset(&mut self, i2c: &mut I) -> Result<(), I::Error> {
+        i2c.write(self.address, &[Command::SoftReset as u8])
+    }
+
+    /// Reads the current humidity
+    ///
+    /// In this mode, the sensor stretches the I2C clock while it takes a measurement. This
+    /// function blocks until the measurement has finished and been read.
+    pub fn read_humidity_blocking(
+        &mut self,
+        i2c: &mut I,
+    ) -> Result<Reading<Humidity>, Error<I::Error>> {
+        self.read_blocking(i2c)
+    }
+
+    /// Reads the current temperature
+    ///
+    /// In this mode, the sensor stretches the I2C clock while it takes a measurement. This
+    /// function blocks until the measurement has finished and been read.
+    pub fn read_temperature_blocking(
+        &mut self,
+        i2c: &mut I,
+    ) -> Result<Reading<Temperature>, Error<I::Error>> {
+        self.read_blocking(i2c)
+    }
+
+    /// Sends a hold master measurement command and reads the result
+    fn read_blocking<M>(&mut self, i2c: &mut I) -> Result<Reading<M>, Error<I::Error>>
+    where
+        M: Measurement,
+    {
+        let command = match M::TYPE {
+            crate::MeasurementType::Temperature => Command::TemperatureHoldMaster,
+            crate::MeasurementType::Humidity => Command::HumidityHoldMaster,
+        };
+        let mut buffer = [0u8; 3];
+        i2c.write_read(self.address, &[command as u8], &mut buffer)
+            .map_err(Error::I2c)?;
+        parse_and_check_reading(&buffer)
+    }
+
+    /// Reads the user register and returns its content
+    pub fn read_user_register(&mut self, i2c: &mut I) -> Result<UserRegister, I::Error> {
+        let mut register_value = 0u8;
+        i2c.write_read(
+            self.address,
+            &[Command::ReadUser as u8],
+            slice::from_mut(&mut register_value),
+        )?;
+        Ok(UserRegister(register_value))
+    }
+
+    /// Writes the user register
+    ///
+    /// You should use the `read_user_register` function to get a `UserRegister` object that
+    /// can be modified and then passed to this function.
+    ///
+    /// # Panics
+    ///
+    /// With debug assertions enabled, this function panics if `register.validate()` returns
+    /// an error.
+    pub fn write_user_register(
+        &mut self,
+        i2c: &mut I,
+        register: UserRegister,
+    ) -> Result<(), I::Error> {
+        debug_assert!(
+            register.validate().is_ok(),
+            "Invalid user register value {:#010b}",
+            register.0
+        );
+        i2c.write(self.address, &[Command::WriteUser as u8, register.0])
+    }
+}
+
+impl<I> Default for Htu2xd<I>
+where
+    I: I2c,
+{
+    fn default() -> Self {
+        Htu2xd::new()
+    }
+}
@@ -0,0 +1,246 @@
+//! An async driver for embedded-hal-async
+//!
+//! This module requires the `async` feature.
+
+use core::marker::PhantomData;
+use core::slice;
+
+use embedded_hal_async::delay::DelayNs;
+use embedded_hal_async::i2c::I2c;
+use futures_util::stream::{self, Stream};
+
+use crate::{
+    conversion_time_ms, parse_and_check_reading, Command, Error, Humidity, Measurement,
+    MeasurementType, Reading, Resolution, Temperature, UserRegister, ADDRESS, SOFT_RESET_TIME_MS,
+};
+
+/// Async HTU2XD driver that does not own the I2C bus
+///
+/// Measurements are taken without clock stretching. After starting a measurement, the driver
+/// awaits the delay for the maximum conversion time and then reads the result, so no polling
+/// is needed.
+///
+/// The driver keeps track of the resolution so that it can wait for the correct conversion
+/// time. It assumes that the sensor has its default resolution when the driver is created.
+/// The resolution is updated when `write_user_register` or `soft_reset` is called. If the
+/// user register is changed in some other way, call `read_user_register` to update the
+/// resolution.
+///
+/// With OTP reload enabled, the sensor restores its default resolution after each
+/// measurement, which would make the cached resolution wrong. For that reason,
+/// `write_user_register` always disables OTP reload.
+pub struct Htu2xdAsync<I> {
+    /// The I2C address of the sensor
+    address: u8,
+    /// The resolution configured in the sensor
+    resolution: Resolution,
+    _i2c: PhantomData<I>,
+}
+
+impl<I> Htu2xdAsync<I>
+where
+    I: I2c,
+{
+    /// Creates a driver object, but does not perform any initialization
+    ///
+    /// The driver uses the sensor's default address, 0x40.
+    pub fn new() -> Self {
+        Htu2xdAsync::new_with_address(ADDRESS)
+    }
+
+    /// Creates a driver object that communicates with a sensor at a non-default I2C address,
+    /// but does not perform any initialization
+    pub fn new_with_address(address: u8) -> Self {
+        Htu2xdAsync {
+            address,
+            resolution: Resolution::Humidity12Temperature14,
+            _i2c: PhantomData,
+        }
+    }
+
+    /// Returns the I2C address that this driver uses
+    pub fn address(&self) -> u8 {
+        self.address
+    }
+
+    /// Resets the sensor and waits for the reset to finish
+    ///
+    /// This restores default settings, but does not restore the heater enable bit.
+    pub async fn soft_reset<D>(&mut self, i2c: &mut I, delay: &mut D) -> Result<(), I::Error>
+    where
+        D: DelayNs,
+    {
+        i2c.write(self.address, &[Command::SoftReset as u8]).await?;
+        delay.delay_ms(SOFT_RESET_TIME_MS).await;
+        self.resolution = Resolution::Humidity12Temperature14;
+        Ok(())
+    }
+
+    /// Measures the humidity
+    ///
+    /// This function waits for the maximum conversion time at the current resolution before
+    /// reading the result.
+    pub async fn read_humidity<D>(
+        &mut self,
+        i2c: &mut I,
+        delay: &mut D,
+    ) -> Result<Reading<Humidity>, Error<I::Error>>
+    where
+        D: DelayNs,
+    {
+        self.measure(i2c, delay).await
+    }
+
+    /// Measures the temperature
+    ///
+    /// This function waits for the maximum conversion time at the current resolution before
+    /// reading the result.
+    pub async fn read_temperature<D>(
+        &mut self,
+        i2c: &mut I,
+        delay: &mut D,
+    ) -> Result<Reading<Temperature>, Error<I::Error>>
+    where
+        D: DelayNs,
+    {
+        self.measure(i2c, delay).await
+    }
+
+    /// Measures the temperature and then the humidity
+    ///
+    /// An off-scale reading is returned as `Error::OffScale`.
+    pub async fn read_climate<D>(
+        &mut self,
+        i2c: &mut I,
+        delay: &mut D,
+    ) -> Result<(Temperature, Humidity), Error<I::Error>>
+    where
+        D: DelayNs,
+    {
+        let temperature = self.read_temperature(i2c, delay).await?.into_value()?;
+        let humidity = self.read_humidity(i2c, delay).await?.into_value()?;
+        Ok((temperature, humidity))
+    }
+
+    /// Returns a stream that measures the temperature and humidity at a fixed interval
+    ///
+    /// Each item is the result of `read_climate`. The first measurement starts immediately,
+    /// and each following measurement starts `interval_ms` milliseconds after the previous
+    /// one started. The time spent on the I2C transfers is not included, so the interval
+    /// can be slightly longer than requested. If `interval_ms` is shorter than the conversion
+    /// times, the measurements are taken one after another without waiting. The stream never
+    /// ends; errors are returned as items.
+    ///
+    /// `delay` is used both for the interval and for the conversion times, so it must
+    /// implement the embedded-hal-async `DelayNs` trait. With Embassy, `embassy_time::Delay`
+    /// implements it.
+    ///
+    /// The stream is not `Unpin`, so it must be pinned (for example with `core::pin::pin!`)
+    /// before calling `StreamExt::next`.
+    pub fn stream<'a, D>(
+        &'a mut self,
+        i2c: &'a mut I,
+        delay: D,
+        interval_ms: u32,
+    ) -> impl Stream<Item = Result<(Temperature, Humidity), Error<I::Error>>> + 'a
+    where
+        D: DelayNs + 'a,
+    {
+        stream::unfold(
+            (self, i2c, delay, false),
+            move |(driver, i2c, mut delay, started)| async move {
+                if started {
+                    let measurement_ms =
+                        conversion_time_ms(&driver.resolution, MeasurementType::Temperature)
+                            + conversion_time_ms(&driver.resolution, MeasurementType::Humidity);
+                    delay
+                        .delay_ms(interval_ms.saturating_sub(measurement_ms))
+                        .await;
+                }
+                let sample = driver.read_climate(i2c, &mut delay).await;
+                Some((sample, (driver, i2c, delay, true)))
+            },
+        )
+    }
+
+    /// Starts a measurement, waits for the conversion time, and reads the result
+    async fn measure<M, D>(
+        &mut self,
+        i2c: &mut I,
+        delay: &mut D,
+    ) -> Result<Reading<M>, Error<I::Error>>
+    where
+        M: Measurement,
+        D: DelayNs,
+    {
+        i2c.write(self.address, &[M::NO_HOLD_COMMAND])
+            .await
+            .map_err(Error::I2c)?;
+        delay
+            .delay_ms(conversion_time_ms(&self.resolution, M::TYPE))
+            .await;
+        let mut buffer = [0u8; 3];
+        i2c.read(self.address, &mut buffer)
+            .await
+            .map_err(Error::I2c)?;
+        parse_and_check_reading(&buffer)
+    }
+
+    /// Reads the user register and returns its content
+    pub async fn read_user_register(&mut self, i2c: &mut I) -> Result<UserRegister, I::Error> {
+        let mut register_value = 0u8;
+        i2c.write_read(
+            self.address,
+            &[Command::ReadUser as u8],
+            slice::from_mut(&mut register_value),
+        )
+        .await?;
+        let register = UserRegister(register_value);
+        self.resolution = if register.otp_reload_enabled() {
+            // The sensor will return to the default resolution after the next measurement,
+            // and the default resolution has the longest conversion times
+            Resolution::Humidity12Temperature14
+        } else {
+            register.resolution()
+        };
+        Ok(register)
+    }
+
+    /// Writes the user register
+    ///
+    /// You should use the `read_user_register` function to get a `UserRegister` object that
+    /// can be modified and then passed to this function.
+    ///
+    /// OTP reload is always disabled in the written value, so that the resolution stays
+    /// as configured and the driver waits for the correct conversion times.
+    ///
+    /// # Panics
+    ///
+    /// With debug assertions enabled, this function panics if `register.validate()` returns
+    /// an error.
+    pub async fn write_user_register(
+        &mut self,
+        i2c: &mut I,
+        register: UserRegister,
+    ) -> Result<(), I::Error> {
+        debug_assert!(
+            register.validate().is_ok(),
+            "Invalid user register value {:#010b}",
+            register.0
+        );
+        let register = register.with_otp_reload_enabled(false);
+        i2c.write(self.address, &[Command::WriteUser as u8, register.0])
+            .await?;
+        self.resolution = register.resolution();
+        Ok(())
+    }
+}
+
+impl<I> Default for Htu2xdAsync<I>
+where
+    I: I2c,
+{
+    fn default() -> Self {
+        Htu2xdAsync::new()
+    }
+}
@@ -1,29 +1,119 @@
 #![no_std]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "hal-02")]
 extern crate embedded_hal;
 extern crate nb;
+#[cfg(feature = "std")]
+extern crate std;
 
+pub mod analysis;
+mod calibration;
 mod crc;
+#[cfg(feature = "environmental")]
+mod environmental;
+#[cfg(feature = "hal-1")]
+pub mod hal_1;
+#[cfg(feature = "async")]
+pub mod hal_async;
+mod nak;
+#[cfg(feature = "hal-02")]
+mod owned;
+mod packing;
+mod psychrometrics;
+mod quality;
+#[cfg(feature = "test-utils")]
+pub mod test_utils;
+mod timing;
+#[cfg(feature = "hal-02")]
+mod tracking;
 mod user_register;
 
-pub use crate::user_register::{Resolution, SupplyVoltage, UserRegister};
+pub use crate::calibration::CorrectionTable;
+pub use crate::crc::{Crc, CrcError, CrcReader};
+#[cfg(feature = "environmental")]
+pub use crate::environmental::{ClimateReading, EnvironmentalReading};
+pub use crate::nak::NakError;
+#[cfg(feature = "hal-02")]
+pub use crate::owned::Htu2xdOwned;
+pub use crate::packing::{pack_climate, unpack_climate};
+#[cfg(feature = "libm")]
+pub use crate::psychrometrics::{
+    absolute_humidity, density_altitude_m, dew_point, dew_point_celsius_stable, frost_point,
+    mixing_ratio_g_per_kg,
+};
+pub use crate::psychrometrics::{comfort_zone, dew_point_decidegrees, ComfortZone};
+pub use crate::quality::{reading_confidence, ClimateSample, Confidence};
+pub use crate::timing::{
+    conversion_time_ms, worst_case_read_time_ms, HUMIDITY_10_BIT_TIME_MS, HUMIDITY_11_BIT_TIME_MS,
+    HUMIDITY_12_BIT_TIME_MS, HUMIDITY_8_BIT_TIME_MS, TEMPERATURE_11_BIT_TIME_MS,
+    TEMPERATURE_12_BIT_TIME_MS, TEMPERATURE_13_BIT_TIME_MS, TEMPERATURE_14_BIT_TIME_MS,
+};
+#[cfg(feature = "hal-02")]
+pub use crate::tracking::TrackingHtu2xd;
+pub use crate::user_register::{
+    Configuration, RegisterError, Resolution, SupplyVoltage, UserRegister,
+};
 
+use core::convert::{TryFrom, TryInto};
+use core::fmt;
 use core::marker::PhantomData;
+#[cfg(feature = "hal-02")]
 use core::slice;
+#[cfg(feature = "hal-02")]
+use core::task::Poll;
 
+#[cfg(feature = "hal-02")]
+use embedded_hal::blocking::delay::DelayMs;
+#[cfg(feature = "hal-02")]
 use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
 
-use crate::crc::Crc;
-
 /// Address of the sensor
+#[cfg(any(feature = "hal-02", feature = "hal-1", feature = "async"))]
 const ADDRESS: u8 = 0x40;
 
+/// Time that `read_climate_auto_heater` keeps the heater enabled to remove condensation,
+/// milliseconds
+pub const HEATER_PULSE_MS: u32 = 1000;
+
+/// Time that `read_climate_auto_heater` waits after disabling the heater before it measures
+/// again, milliseconds
+///
+/// The heater warms the sensor by a few degrees. Measuring before the sensor has cooled to
+/// the ambient temperature would report a temperature that is too high and a humidity that
+/// is too low.
+pub const HEATER_COOLDOWN_MS: u32 = 2000;
+
+/// Command bytes that read the first part of the serial number
+#[cfg(feature = "hal-02")]
+const SERIAL_NUMBER_FIRST_COMMAND: [u8; 2] = [0xfa, 0x0f];
+/// Command bytes that read the second part of the serial number
+#[cfg(feature = "hal-02")]
+const SERIAL_NUMBER_SECOND_COMMAND: [u8; 2] = [0xfc, 0xc9];
+/// Command bytes that read the firmware revision
+#[cfg(feature = "hal-02")]
+const FIRMWARE_REVISION_COMMAND: [u8; 2] = [0x84, 0xb8];
+
+/// Humidity temperature coefficient from the datasheet, percent relative humidity per degree
+/// Celsius
+const HUMIDITY_TEMPERATURE_COEFFICIENT: f32 = -0.15;
+
+/// Maximum time for a soft reset to finish, milliseconds
+#[cfg(any(feature = "hal-02", feature = "async"))]
+const SOFT_RESET_TIME_MS: u32 = 15;
+
 mod sealed {
-    pub trait SealedFromRaw {
+    pub trait SealedMeasurement {
+        /// The quantity that this type represents
+        const TYPE: crate::MeasurementType;
+        /// Command that starts a measurement without clock stretching
+        const NO_HOLD_COMMAND: u8;
         fn from_raw(raw: u16) -> Self;
+        fn raw(&self) -> u16;
     }
 }
-use self::sealed::SealedFromRaw;
+use self::sealed::SealedMeasurement;
 
 /// HTU2XD driver that does not own the I2C bus
 ///
@@ -39,6 +129,8 @@ use self::sealed::SealedFromRaw;
 /// ## Configuration
 ///
 /// ```no_run
+/// # #[cfg(feature = "hal-02")]
+/// # mod example {
 /// use embedded_hal::blocking::delay::DelayMs;
 /// use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
 /// use htu2xd::{Htu2xd, Resolution};
@@ -60,11 +152,14 @@ use self::sealed::SealedFromRaw;
 ///
 ///     Ok(htu)
 /// }
+/// # }
 /// ```
 ///
 /// ## Basic operation
 ///
 /// ```no_run
+/// # #[cfg(feature = "hal-02")]
+/// # mod example {
 /// use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
 /// use htu2xd::{Htu2xd, Reading, Temperature};
 /// fn use_sensor<I, E>(htu: &mut Htu2xd<I>, i2c: &mut I) -> Result<(), htu2xd::Error<E>>
@@ -87,13 +182,16 @@ use self::sealed::SealedFromRaw;
 ///     }
 ///     Ok(())
 /// }
+/// # }
 /// ```
 ///
 /// ## Temperature and humidity reading without clock stretching
 ///
 /// ```no_run
+/// # #[cfg(feature = "hal-02")]
+/// # mod example {
 /// use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
-/// use htu2xd::{Htu2xd, Reading, Temperature};
+/// use htu2xd::{Htu2xd, ReadAttempt, Reading, Temperature};
 ///
 /// enum I2cError {
 ///     Nak,
@@ -113,7 +211,12 @@ use self::sealed::SealedFromRaw;
 ///     let mut temperature_step2 = htu.read_temperature(i2c)?;
 ///     // Do something else while the sensor is busy
 ///     // Later, read the result
-///     let temperature_reading = nb::block!(temperature_step2.read_result(i2c, I2cError::is_nak))?;
+///     let temperature_reading = loop {
+///         match temperature_step2.read_result(i2c, I2cError::is_nak)? {
+///             ReadAttempt::Pending(reader) => temperature_step2 = reader,
+///             ReadAttempt::Done(reading) => break reading,
+///         }
+///     };
 ///     match temperature_reading {
 ///         Reading::Ok(reading) => {
 ///             println!("Temperature {} degrees C", reading.as_degrees_celsius())
@@ -124,7 +227,12 @@ use self::sealed::SealedFromRaw;
 ///     let mut humidity_step2 = htu.read_humidity(i2c)?;
 ///     // Do something else while the sensor is busy
 ///     // Later, read the result
-///     let humidity_reading = nb::block!(humidity_step2.read_result(i2c, I2cError::is_nak))?;
+///     let humidity_reading = loop {
+///         match humidity_step2.read_result(i2c, I2cError::is_nak)? {
+///             ReadAttempt::Pending(reader) => humidity_step2 = reader,
+///             ReadAttempt::Done(reading) => break reading,
+///         }
+///     };
 ///     match humidity_reading {
 ///         Reading::Ok(reading) => println!("Humidity {}%", reading.as_percent_relative()),
 ///         Reading::ErrorLow => println!("Humidity off-scale low or sensor error"),
@@ -132,32 +240,104 @@ use self::sealed::SealedFromRaw;
 ///     }
 ///     Ok(())
 /// }
+/// # }
 /// ```
-pub struct Htu2xd<I>(PhantomData<I>);
+pub struct Htu2xd<I> {
+    /// The I2C address of the sensor
+    #[cfg_attr(not(feature = "hal-02"), allow(dead_code))]
+    address: u8,
+    _i2c: PhantomData<I>,
+}
 
+#[cfg(feature = "hal-02")]
 impl<I, E> Htu2xd<I>
 where
     I: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
 {
     /// Creates a driver object, but does not perform any initialization
+    ///
+    /// The driver uses the sensor's default address, 0x40.
     pub fn new() -> Self {
-        Htu2xd(PhantomData)
+        Htu2xd::new_with_address(ADDRESS)
+    }
+
+    /// Creates a driver object that communicates with a sensor at a non-default I2C address,
+    /// but does not perform any initialization
+    ///
+    /// This is useful when the address is remapped by other hardware between the
+    /// microcontroller and the sensor.
+    pub fn new_with_address(address: u8) -> Self {
+        Htu2xd {
+            address,
+            _i2c: PhantomData,
+        }
+    }
+
+    /// Returns the I2C address that this driver uses
+    pub fn address(&self) -> u8 {
+        self.address
     }
 
     /// Resets the sensor and restores default settings, but does not restore the heater enable bit
     ///
     /// After this function returns the sensor may take up to 15 ms to reset.
     pub fn soft_reset(&mut self, i2c: &mut I) -> Result<(), E> {
-        i2c.write(ADDRESS, &[Command::SoftReset as u8])
+        i2c.write(self.address, &[Command::SoftReset as u8])
+    }
+
+    /// Resets the sensor, waits for the reset to finish, and checks that the sensor is
+    /// responding with its reset settings
+    ///
+    /// This function returns `Error::ResetFailed` if the sensor does not respond after the
+    /// reset, or if the user register does not contain the default resolution and valid
+    /// reserved bits. The heater enable bit is not checked because a soft reset does not
+    /// change it.
+    pub fn soft_reset_verified<D>(&mut self, i2c: &mut I, delay: &mut D) -> Result<(), Error<E>>
+    where
+        D: DelayMs<u32>,
+    {
+        self.soft_reset(i2c)?;
+        delay.delay_ms(SOFT_RESET_TIME_MS);
+
+        let register = self
+            .read_user_register(i2c)
+            .map_err(|_| Error::ResetFailed)?;
+        if register.validate().is_err()
+            || register.resolution() != Configuration::default().resolution
+        {
+            return Err(Error::ResetFailed);
+        }
+        Ok(())
+    }
+
+    /// Checks that a sensor is responding with a plausible user register value
+    ///
+    /// This function reads the user register and returns true if the reserved bits are valid
+    /// and the value is not all zeros or all ones. It returns false if the value does not
+    /// look like it came from an HTU2XD. I2C errors, such as a NAK when no device is present
+    /// at the sensor's address, are returned as errors.
+    pub fn probe(&mut self, i2c: &mut I) -> Result<bool, E> {
+        let register = self.read_user_register(i2c)?;
+        Ok(register.validate().is_ok() && register.0 != 0x00 && register.0 != 0xff)
     }
 
     /// Reads the current humidity
     ///
     /// In this mode, the sensor stretches the I2C clock while it takes a measurement. This
     /// function blocks until the measurement has finished and been read.
+    ///
+    /// If the sensor is faulty, it may stretch the clock indefinitely. Unless the I2C driver
+    /// has a timeout, this function will then never return. With an I2C driver that reports
+    /// timeouts as errors, use `read_humidity_blocking_timeout` instead. Otherwise, if a stuck
+    /// sensor must not stop the program, use `read_humidity` or `read_climate`, which do not
+    /// use clock stretching.
     pub fn read_humidity_blocking(&mut self, i2c: &mut I) -> Result<Reading<Humidity>, Error<E>> {
         let mut buffer = [0u8; 3];
-        i2c.write_read(ADDRESS, &[Command::HumidityHoldMaster as u8], &mut buffer)?;
+        i2c.write_read(
+            self.address,
+            &[Command::HumidityHoldMaster as u8],
+            &mut buffer,
+        )?;
         parse_and_check_reading(&buffer)
     }
 
@@ -165,211 +345,1965 @@ where
     ///
     /// In this mode, the sensor stretches the I2C clock while it takes a measurement. This
     /// function blocks until the measurement has finished and been read.
+    ///
+    /// If the sensor is faulty, it may stretch the clock indefinitely. Unless the I2C driver
+    /// has a timeout, this function will then never return. With an I2C driver that reports
+    /// timeouts as errors, use `read_temperature_blocking_timeout` instead. Otherwise, if a stuck
+    /// sensor must not stop the program, use `read_temperature` or `read_climate`, which do not
+    /// use clock stretching.
     pub fn read_temperature_blocking(
         &mut self,
         i2c: &mut I,
     ) -> Result<Reading<Temperature>, Error<E>> {
         let mut buffer = [0u8; 3];
         i2c.write_read(
-            ADDRESS,
+            self.address,
             &[Command::TemperatureHoldMaster as u8],
             &mut buffer,
         )?;
         parse_and_check_reading(&buffer)
     }
 
-    /// Reads the current humidity
+    /// Reads the current temperature and then the current humidity
     ///
-    /// In this mode, the sensor does not stretch the I2C clock. After sending the command to
-    /// the sensor, this function returns a proxy that can be polled to determine if the result
-    /// is ready.
-    pub fn read_humidity(&mut self, i2c: &mut I) -> Result<ResultReader<I, Humidity>, E> {
-        // Send a command to start the read
-        i2c.write(ADDRESS, &[Command::Humidity as u8])?;
-        Ok(ResultReader {
-            _driver: PhantomData,
-            _reading: PhantomData,
-        })
+    /// This works like `read_temperature_blocking` followed by `read_humidity_blocking`, and
+    /// has the same risk of blocking indefinitely if the sensor is faulty. Because the two
+    /// measurements are taken immediately one after the other, they are suitable for
+    /// calculations that need both, such as the dew point.
+    pub fn read_measurements_blocking(
+        &mut self,
+        i2c: &mut I,
+    ) -> Result<(Reading<Temperature>, Reading<Humidity>), Error<E>> {
+        let temperature = self.read_temperature_blocking(i2c)?;
+        let humidity = self.read_humidity_blocking(i2c)?;
+        Ok((temperature, humidity))
     }
 
-    /// Reads the current temperature
+    /// Reads the current humidity and returns both the raw value and the humidity in percent
     ///
-    /// In this mode, the sensor does not stretch the I2C clock. After sending the command to
-    /// the sensor, this function returns a proxy that can be polled to determine if the result
-    /// is ready.
-    pub fn read_temperature(&mut self, i2c: &mut I) -> Result<ResultReader<I, Temperature>, E> {
-        // Send a command to start the read
-        i2c.write(ADDRESS, &[Command::Temperature as u8])?;
-        Ok(ResultReader {
-            _driver: PhantomData,
-            _reading: PhantomData,
+    /// This works like `read_humidity_blocking`. A valid reading contains the value from
+    /// `Humidity::as_raw` and the value from `Humidity::as_percent_relative`.
+    pub fn read_humidity_detailed_blocking(
+        &mut self,
+        i2c: &mut I,
+    ) -> Result<Reading<(u16, f32)>, Error<E>> {
+        Ok(match self.read_humidity_blocking(i2c)? {
+            Reading::Ok(humidity) => {
+                Reading::Ok((humidity.as_raw(), humidity.as_percent_relative()))
+            }
+            Reading::ErrorLow => Reading::ErrorLow,
+            Reading::ErrorHigh => Reading::ErrorHigh,
         })
     }
 
-    /// Reads the user register and returns its content
-    pub fn read_user_register(&mut self, i2c: &mut I) -> Result<UserRegister, E> {
-        let mut register_value = 0u8;
-        i2c.write_read(
-            ADDRESS,
-            &[Command::ReadUser as u8],
-            slice::from_mut(&mut register_value),
-        )?;
-        Ok(UserRegister(register_value))
+    /// Reads the current temperature and returns both the raw value and the temperature in
+    /// degrees Celsius
+    ///
+    /// This works like `read_temperature_blocking`. A valid reading contains the value from
+    /// `Temperature::as_raw` and the value from `Temperature::as_degrees_celsius`.
+    pub fn read_temperature_detailed_blocking(
+        &mut self,
+        i2c: &mut I,
+    ) -> Result<Reading<(u16, f32)>, Error<E>> {
+        Ok(match self.read_temperature_blocking(i2c)? {
+            Reading::Ok(temperature) => {
+                Reading::Ok((temperature.as_raw(), temperature.as_degrees_celsius()))
+            }
+            Reading::ErrorLow => Reading::ErrorLow,
+            Reading::ErrorHigh => Reading::ErrorHigh,
+        })
     }
 
-    /// Writes the user register
+    /// Reads the current humidity, using an I2C driver timeout to detect a stuck sensor
     ///
-    /// You must use the `read_user_register` function to get a `UserRegister` object that
-    /// can be modified and then passed to this function.
-    pub fn write_user_register(&mut self, i2c: &mut I, register: UserRegister) -> Result<(), E> {
-        i2c.write(ADDRESS, &[Command::WriteUser as u8, register.0])
+    /// This works like `read_humidity_blocking`. The I2C driver must have a timeout that
+    /// limits how long the sensor can stretch the clock. `is_timeout` should return true if
+    /// an I2C error was caused by that timeout. In that case, this function returns
+    /// `Error::Timeout`. Other I2C errors are returned as `Error::I2c`.
+    ///
+    /// The timeout should be longer than the maximum conversion time at the configured
+    /// resolution (see `conversion_time_ms`).
+    pub fn read_humidity_blocking_timeout<F>(
+        &mut self,
+        i2c: &mut I,
+        is_timeout: F,
+    ) -> Result<Reading<Humidity>, Error<E>>
+    where
+        F: FnOnce(&E) -> bool,
+    {
+        self.read_blocking_timeout(i2c, Command::HumidityHoldMaster, is_timeout)
     }
-}
 
-impl<I, E> Default for Htu2xd<I>
-where
-    I: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
-{
-    fn default() -> Self {
-        Htu2xd::new()
+    /// Reads the current temperature, using an I2C driver timeout to detect a stuck sensor
+    ///
+    /// This works like `read_temperature_blocking`. The I2C driver must have a timeout that
+    /// limits how long the sensor can stretch the clock. `is_timeout` should return true if
+    /// an I2C error was caused by that timeout. In that case, this function returns
+    /// `Error::Timeout`. Other I2C errors are returned as `Error::I2c`.
+    ///
+    /// The timeout should be longer than the maximum conversion time at the configured
+    /// resolution (see `conversion_time_ms`).
+    pub fn read_temperature_blocking_timeout<F>(
+        &mut self,
+        i2c: &mut I,
+        is_timeout: F,
+    ) -> Result<Reading<Temperature>, Error<E>>
+    where
+        F: FnOnce(&E) -> bool,
+    {
+        self.read_blocking_timeout(i2c, Command::TemperatureHoldMaster, is_timeout)
     }
-}
 
-/// A proxy used to read the result of a non-blocking measurement
-pub struct ResultReader<'h, I, M> {
-    _driver: PhantomData<&'h mut Htu2xd<I>>,
-    _reading: PhantomData<M>,
-}
+    /// Sends a hold master measurement command and reads the result, converting timeout
+    /// errors into `Error::Timeout`
+    fn read_blocking_timeout<M, F>(
+        &mut self,
+        i2c: &mut I,
+        command: Command,
+        is_timeout: F,
+    ) -> Result<Reading<M>, Error<E>>
+    where
+        M: Measurement,
+        F: FnOnce(&E) -> bool,
+    {
+        let mut buffer = [0u8; 3];
+        match i2c.write_read(self.address, &[command as u8], &mut buffer) {
+            Ok(()) => parse_and_check_reading(&buffer),
+            Err(e) if is_timeout(&e) => Err(Error::Timeout),
+            Err(e) => Err(Error::I2c(e)),
+        }
+    }
 
-impl<'h, I, M> ResultReader<'h, I, M>
-where
-    I: Read,
-    M: Measurement,
-{
-    /// Attempts to read a measurement result from the sensor
+    /// Reads the current humidity using a buffer provided by the caller
     ///
-    /// is_nak must be a closure that returns true if the provided error is a NAK (negative
-    /// acknowledge) error, or false otherwise.
+    /// This works like `read_humidity_blocking`, but the sensor response is read into the
+    /// first 3 bytes of `buffer`. This can be used to place the buffer in memory that is
+    /// suitable for DMA.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `buffer` is shorter than 3 bytes.
+    pub fn read_humidity_blocking_into(
+        &mut self,
+        i2c: &mut I,
+        buffer: &mut [u8],
+    ) -> Result<Reading<Humidity>, Error<E>> {
+        self.read_blocking_into(i2c, Command::HumidityHoldMaster, buffer)
+    }
+
+    /// Reads the current temperature using a buffer provided by the caller
     ///
-    /// This function returns `Err(nb::Error::WouldBlock)` if the sensor does not acknowledge
-    /// its address. This means that it is still performing the measurement. This function should
-    /// be called again later to try again.
+    /// This works like `read_temperature_blocking`, but the sensor response is read into the
+    /// first 3 bytes of `buffer`. This can be used to place the buffer in memory that is
+    /// suitable for DMA.
     ///
-    /// On success, this function returns the sensor reading.
+    /// # Panics
     ///
-    /// After this function returns anything other than `Err(nb::Error::WouldBlock)`, this
-    /// `ResultReader` must not be used again.
-    pub fn read_result<F>(
+    /// This function panics if `buffer` is shorter than 3 bytes.
+    pub fn read_temperature_blocking_into(
         &mut self,
         i2c: &mut I,
-        is_nak: F,
-    ) -> nb::Result<Reading<M>, Error<I::Error>>
+        buffer: &mut [u8],
+    ) -> Result<Reading<Temperature>, Error<E>> {
+        self.read_blocking_into(i2c, Command::TemperatureHoldMaster, buffer)
+    }
+
+    /// Sends a hold master measurement command and reads the result into the first 3 bytes
+    /// of a buffer
+    fn read_blocking_into<M>(
+        &mut self,
+        i2c: &mut I,
+        command: Command,
+        buffer: &mut [u8],
+    ) -> Result<Reading<M>, Error<E>>
     where
-        F: FnOnce(&I::Error) -> bool,
+        M: Measurement,
     {
-        let mut buffer = [0u8; 3];
-        match i2c.read(ADDRESS, &mut buffer[..]) {
-            Ok(()) => parse_and_check_reading(&buffer).map_err(nb::Error::Other),
-            Err(e) => {
-                if is_nak(&e) {
-                    // Measurement is still in progress, try again later
-                    Err(nb::Error::WouldBlock)
-                } else {
-                    // Some other error, not a NAK
-                    Err(nb::Error::Other(Error::I2c(e)))
-                }
-            }
-        }
+        let frame = &mut buffer[..3];
+        i2c.write_read(self.address, &[command as u8], frame)?;
+        parse_and_check_reading(&[frame[0], frame[1], frame[2]])
     }
-}
 
-/// Checks the CRC of a 3-byte temperature or humidity reading and parses it as a `Reading` object
-fn parse_and_check_reading<M, E>(bytes: &[u8; 3]) -> Result<Reading<M>, Error<E>>
-where
-    M: Measurement,
-{
-    // Check CRC
-    let mut crc = Crc::new();
-    crc.add_all(&*bytes);
-    if crc.value() != 0 {
-        return Err(Error::Crc);
+    /// Reads the current humidity, sending the command and reading the result in two
+    /// separate I2C transactions
+    ///
+    /// This works like `read_humidity_blocking`, but uses a write followed by a read instead
+    /// of a combined write-read transaction. This is useful with I2C bridges and multiplexers
+    /// that do not support combined transactions.
+    pub fn read_humidity_blocking_separate(
+        &mut self,
+        i2c: &mut I,
+    ) -> Result<Reading<Humidity>, Error<E>> {
+        self.read_blocking_separate(i2c, Command::HumidityHoldMaster)
     }
 
-    // Parse reading
-    let reading16 = (u16::from(bytes[0]) << 8) | u16::from(bytes[1]);
-
-    Ok(Reading::from_raw(reading16))
-}
-
-/// An I2C or CRC error
-#[derive(Debug)]
-pub enum Error<E> {
-    /// The I2C driver returned an error
-    I2c(E),
-    /// A message was received from the sensor with an invalid CRC checksum
-    Crc,
-}
+    /// Reads the current temperature, sending the command and reading the result in two
+    /// separate I2C transactions
+    ///
+    /// This works like `read_temperature_blocking`, but uses a write followed by a read
+    /// instead of a combined write-read transaction. This is useful with I2C bridges and
+    /// multiplexers that do not support combined transactions.
+    pub fn read_temperature_blocking_separate(
+        &mut self,
+        i2c: &mut I,
+    ) -> Result<Reading<Temperature>, Error<E>> {
+        self.read_blocking_separate(i2c, Command::TemperatureHoldMaster)
+    }
 
-impl<E> From<E> for Error<E> {
-    fn from(inner: E) -> Self {
-        Error::I2c(inner)
+    /// Sends a hold master measurement command, then reads the result in another transaction
+    fn read_blocking_separate<M>(
+        &mut self,
+        i2c: &mut I,
+        command: Command,
+    ) -> Result<Reading<M>, Error<E>>
+    where
+        M: Measurement,
+    {
+        i2c.write(self.address, &[command as u8])?;
+        let mut buffer = [0u8; 3];
+        i2c.read(self.address, &mut buffer)?;
+        parse_and_check_reading(&buffer)
     }
-}
 
-/// A temperature reading
-#[derive(Debug, Clone)]
-pub struct Temperature(u16);
+    /// Reads the current humidity and returns an error if the reading is off-scale
+    ///
+    /// This function works like `read_humidity_blocking`, but returns
+    /// `Error::OffScale` instead of `Reading::ErrorLow` or `Reading::ErrorHigh`.
+    pub fn read_humidity_value_blocking(&mut self, i2c: &mut I) -> Result<Humidity, Error<E>> {
+        self.read_humidity_blocking(i2c)?.into_value()
+    }
 
-impl Temperature {
-    /// Returns the temperature reading exactly as read from the sensor, with the status bits
-    /// cleared
-    pub fn as_raw(&self) -> u16 {
-        self.0
+    /// Reads the current temperature and returns an error if the reading is off-scale
+    ///
+    /// This function works like `read_temperature_blocking`, but returns
+    /// `Error::OffScale` instead of `Reading::ErrorLow` or `Reading::ErrorHigh`.
+    pub fn read_temperature_value_blocking(
+        &mut self,
+        i2c: &mut I,
+    ) -> Result<Temperature, Error<E>> {
+        self.read_temperature_blocking(i2c)?.into_value()
     }
 
-    /// Converts the temperature reading into degrees Celsius
+    /// Measures the temperature and then the humidity, without clock stretching
     ///
-    /// This function uses single-precision floating-point operations.
-    pub fn as_degrees_celsius(&self) -> f32 {
-        -46.85_f32 + 175.72_f32 / 65536.0_f32 * f32::from(self.0)
+    /// After starting each measurement, this function waits for the maximum conversion time
+    /// at the highest resolution before reading the result. An off-scale reading is returned
+    /// as `Error::OffScale`.
+    pub fn read_climate<D>(
+        &mut self,
+        i2c: &mut I,
+        delay: &mut D,
+    ) -> Result<(Temperature, Humidity), Error<E>>
+    where
+        D: DelayMs<u32>,
+    {
+        let resolution = Resolution::Humidity12Temperature14;
+        let temperature = self
+            .measure_with_delay(i2c, delay, &resolution)?
+            .into_value()?;
+        let humidity = self
+            .measure_with_delay(i2c, delay, &resolution)?
+            .into_value()?;
+        Ok((temperature, humidity))
     }
-}
 
-/// A humidity reading
-#[derive(Debug, Clone)]
-pub struct Humidity(u16);
+    /// Measures the temperature and then the humidity, each at a different resolution
+    ///
+    /// The sensor has one resolution setting for both measurements. This function writes
+    /// the user register to select `temperature_resolution`, measures the temperature,
+    /// writes the user register again to select `humidity_resolution`, and measures the
+    /// humidity. The temperature resolution only affects temperature measurements, and the
+    /// humidity resolution only affects humidity measurements.
+    ///
+    /// After measuring, this function writes back the original user register value, even if
+    /// a measurement failed. Compared to `read_climate`, this adds one user register read and
+    /// three user register writes on the bus. Each measurement waits only for the conversion
+    /// time at its selected resolution.
+    pub fn read_with_resolutions<D>(
+        &mut self,
+        i2c: &mut I,
+        delay: &mut D,
+        temperature_resolution: Resolution,
+        humidity_resolution: Resolution,
+    ) -> Result<(Temperature, Humidity), Error<E>>
+    where
+        D: DelayMs<u32>,
+    {
+        let original = self.read_user_register_raw(i2c)?;
+        let result = self.measure_with_resolutions(
+            i2c,
+            delay,
+            original,
+            temperature_resolution,
+            humidity_resolution,
+        );
+        self.write_user_register(i2c, UserRegister(original))?;
+        result
+    }
 
-impl Humidity {
-    /// Returns the humidity reading exactly as read from the sensor, with the status bits cleared
-    pub fn as_raw(&self) -> u16 {
-        self.0
+    /// Measures the temperature and then the humidity, changing the resolution in the user
+    /// register before each measurement
+    fn measure_with_resolutions<D>(
+        &mut self,
+        i2c: &mut I,
+        delay: &mut D,
+        original: u8,
+        temperature_resolution: Resolution,
+        humidity_resolution: Resolution,
+    ) -> Result<(Temperature, Humidity), Error<E>>
+    where
+        D: DelayMs<u32>,
+    {
+        self.write_user_register(
+            i2c,
+            UserRegister(original).with_resolution(temperature_resolution.clone()),
+        )?;
+        let temperature = self
+            .measure_with_delay(i2c, delay, &temperature_resolution)?
+            .into_value()?;
+        self.write_user_register(
+            i2c,
+            UserRegister(original).with_resolution(humidity_resolution.clone()),
+        )?;
+        let humidity = self
+            .measure_with_delay(i2c, delay, &humidity_resolution)?
+            .into_value()?;
+        Ok((temperature, humidity))
     }
 
-    /// Converts the temperature reading into percent relative humidity (0.0 = 0%, 100.0 = 100%)
-    /// and clamps it to the 0%-100% range
+    /// Measures the temperature and humidity, and uses the heater to remove condensation if
+    /// the humidity is high
     ///
-    /// This function uses single-precision floating-point operations.
-    pub fn as_percent_relative(&self) -> f32 {
-        -6.0_f32 + 125.0_f32 / 65536.0_f32 * f32::from(self.0)
+    /// This function first measures like `read_climate`. If the humidity is at least
+    /// `humidity_threshold_pct` percent, condensation may have formed on the sensor. In that
+    /// case, this function enables the heater for `HEATER_PULSE_MS` milliseconds, restores
+    /// the previous heater setting, waits `HEATER_COOLDOWN_MS` milliseconds for the sensor to
+    /// cool down, and measures the temperature and humidity again.
+    ///
+    /// When the heater is used, this function takes more than three seconds longer than
+    /// `read_climate`.
+    pub fn read_climate_auto_heater<D>(
+        &mut self,
+        i2c: &mut I,
+        delay: &mut D,
+        humidity_threshold_pct: f32,
+    ) -> Result<(Temperature, Humidity), Error<E>>
+    where
+        D: DelayMs<u32>,
+    {
+        let (temperature, humidity) = self.read_climate(i2c, delay)?;
+        if humidity.as_percent_relative_unclamped() < humidity_threshold_pct {
+            return Ok((temperature, humidity));
+        }
+
+        let register = self.read_user_register(i2c)?;
+        let original = register.0;
+        self.write_user_register(i2c, register.with_heater_enabled(true))?;
+        delay.delay_ms(HEATER_PULSE_MS);
+        self.write_user_register(i2c, UserRegister(original))?;
+        delay.delay_ms(HEATER_COOLDOWN_MS);
+
+        self.read_climate(i2c, delay)
     }
-}
 
-pub trait Measurement: SealedFromRaw {}
-impl SealedFromRaw for Temperature {
-    fn from_raw(raw: u16) -> Self {
-        Temperature(raw)
+    /// Measures the temperature several times and returns the average
+    ///
+    /// This function takes `oversample` measurements (at least one) in the same way as
+    /// `read_climate`, adds their raw values, and divides the sum by the number of valid
+    /// measurements, rounding to the nearest integer. Off-scale measurements are skipped.
+    /// If every measurement is off-scale, this function returns `Error::OffScale` for the
+    /// last one.
+    ///
+    /// The two least significant bits of the average are not cleared, so the result can have
+    /// more resolution than a single measurement.
+    pub fn read_temperature_oversampled<D>(
+        &mut self,
+        i2c: &mut I,
+        delay: &mut D,
+        oversample: u8,
+    ) -> Result<Temperature, Error<E>>
+    where
+        D: DelayMs<u32>,
+    {
+        let resolution = Resolution::Humidity12Temperature14;
+        let mut sum = 0u32;
+        let mut count = 0u32;
+        let mut last_error = None;
+        for _ in 0..oversample.max(1) {
+            match self
+                .measure_with_delay::<Temperature, D>(i2c, delay, &resolution)?
+                .into_value()
+            {
+                Ok(temperature) => {
+                    sum += u32::from(temperature.0);
+                    count += 1;
+                }
+                Err(e) => last_error = Some(e),
+            }
+        }
+        match last_error {
+            Some(e) if count == 0 => Err(e),
+            _ => Ok(Temperature(((sum + count / 2) / count) as u16)),
+        }
     }
-}
-impl Measurement for Temperature {}
-impl SealedFromRaw for Humidity {
-    fn from_raw(raw: u16) -> Self {
-        Humidity(raw)
+
+    /// Checks if the sensor can return the temperature from the previous humidity measurement
+    ///
+    /// Some sensors measure the temperature during each humidity measurement, and return that
+    /// temperature in response to command 0xe0. This function measures the humidity in the
+    /// same way as `read_climate`, then sends command 0xe0 and reads a 3-byte result.
+    /// It returns true if the result has a valid CRC and is not off-scale.
+    ///
+    /// An I2C error in response to command 0xe0 (for example, if the sensor does not
+    /// acknowledge the command) is treated as a false result. Errors during the humidity
+    /// measurement are returned as errors.
+    ///
+    /// If this function returns false, the temperature must be measured separately.
+    pub fn supports_cached_temperature<D>(
+        &mut self,
+        i2c: &mut I,
+        delay: &mut D,
+    ) -> Result<bool, Error<E>>
+    where
+        D: DelayMs<u32>,
+    {
+        self.measure_with_delay::<Humidity, D>(i2c, delay, &Resolution::Humidity12Temperature14)?;
+        Ok(matches!(
+            self.read_temperature_from_previous_humidity(i2c),
+            Ok(Reading::Ok(_))
+        ))
     }
-}
-impl Measurement for Humidity {}
 
-/// Information about a temperature or humidity reading
+    /// Reads the temperature that the sensor measured during the previous humidity
+    /// measurement
+    ///
+    /// This sends command 0xe0 and reads a 3-byte result. It does not start a new
+    /// measurement, so it is faster than a temperature measurement and does not heat the
+    /// sensor.
+    ///
+    /// This function must be called after a humidity measurement has finished. The driver
+    /// does not keep track of previous measurements, so it cannot check this. Not all sensors
+    /// support this command; use `supports_cached_temperature` to check.
+    pub fn read_temperature_from_previous_humidity(
+        &mut self,
+        i2c: &mut I,
+    ) -> Result<Reading<Temperature>, Error<E>> {
+        let mut buffer = [0u8; 3];
+        i2c.write_read(
+            self.address,
+            &[Command::TemperatureFromHumidity as u8],
+            &mut buffer,
+        )?;
+        parse_and_check_reading(&buffer)
+    }
+
+    /// Checks that the heater works by measuring how much it warms the sensor
+    ///
+    /// This function measures the temperature, enables the heater for `HEATER_PULSE_MS`
+    /// milliseconds, measures the temperature again with the heater on, and then restores
+    /// the previous heater setting (even if the second measurement failed). It returns the
+    /// temperature rise in degrees Celsius. A rise close to zero suggests that the heater
+    /// does not work.
+    ///
+    /// The measurements are taken in the same way as `read_climate`.
+    pub fn heater_self_test<D>(&mut self, i2c: &mut I, delay: &mut D) -> Result<f32, Error<E>>
+    where
+        D: DelayMs<u32>,
+    {
+        let resolution = Resolution::Humidity12Temperature14;
+        let before: Temperature = self
+            .measure_with_delay(i2c, delay, &resolution)?
+            .into_value()?;
+
+        let register = self.read_user_register(i2c)?;
+        let original = register.0;
+        self.write_user_register(i2c, register.with_heater_enabled(true))?;
+        delay.delay_ms(HEATER_PULSE_MS);
+        let after = self
+            .measure_with_delay::<Temperature, D>(i2c, delay, &resolution)
+            .and_then(Reading::into_value);
+        self.write_user_register(i2c, UserRegister(original))?;
+
+        Ok(after?.as_degrees_celsius() - before.as_degrees_celsius())
+    }
+
+    /// Measures the temperature and humidity and estimates their uncertainties
+    ///
+    /// The measurements are taken in the same way as `read_climate`. This function then reads
+    /// the user register to get the resolution and the supply voltage during the measurements.
+    /// See `ClimateSample::new` for details of the uncertainty estimates.
+    pub fn read_climate_with_uncertainty<D>(
+        &mut self,
+        i2c: &mut I,
+        delay: &mut D,
+    ) -> Result<ClimateSample, Error<E>>
+    where
+        D: DelayMs<u32>,
+    {
+        let (temperature, humidity) = self.read_climate(i2c, delay)?;
+        let register = self.read_user_register(i2c)?;
+        Ok(ClimateSample::new(
+            temperature,
+            humidity,
+            &register.resolution(),
+            &register.supply_voltage(),
+        ))
+    }
+
+    /// Measures the temperature and humidity and classifies them using `comfort_zone`
+    ///
+    /// The measurements are taken in the same way as `read_climate`.
+    pub fn read_comfort<D>(
+        &mut self,
+        i2c: &mut I,
+        delay: &mut D,
+    ) -> Result<(Temperature, Humidity, ComfortZone), Error<E>>
+    where
+        D: DelayMs<u32>,
+    {
+        let (temperature, humidity) = self.read_climate(i2c, delay)?;
+        let zone = comfort_zone(&temperature, &humidity);
+        Ok((temperature, humidity, zone))
+    }
+
+    /// Starts a measurement, waits for the maximum conversion time at a resolution, and reads
+    /// the result
+    fn measure_with_delay<M, D>(
+        &mut self,
+        i2c: &mut I,
+        delay: &mut D,
+        resolution: &Resolution,
+    ) -> Result<Reading<M>, Error<E>>
+    where
+        M: Measurement,
+        D: DelayMs<u32>,
+    {
+        i2c.write(self.address, &[M::NO_HOLD_COMMAND])?;
+        delay.delay_ms(conversion_time_ms(resolution, M::TYPE));
+        let mut buffer = [0u8; 3];
+        i2c.read(self.address, &mut buffer)?;
+        parse_and_check_reading(&buffer)
+    }
+
+    /// Reads the current humidity
+    ///
+    /// In this mode, the sensor does not stretch the I2C clock. After sending the command to
+    /// the sensor, this function returns a proxy that can be polled to determine if the result
+    /// is ready.
+    pub fn read_humidity(&mut self, i2c: &mut I) -> Result<ResultReader<'_, I, Humidity>, E> {
+        self.start_measurement(i2c)
+    }
+
+    /// Reads the current temperature
+    ///
+    /// In this mode, the sensor does not stretch the I2C clock. After sending the command to
+    /// the sensor, this function returns a proxy that can be polled to determine if the result
+    /// is ready.
+    pub fn read_temperature(&mut self, i2c: &mut I) -> Result<ResultReader<'_, I, Temperature>, E> {
+        self.start_measurement(i2c)
+    }
+
+    /// Starts measuring temperature or humidity, depending on the type `M`
+    ///
+    /// In this mode, the sensor does not stretch the I2C clock. After sending the command to
+    /// the sensor, this function returns a proxy that can be polled to determine if the result
+    /// is ready.
+    pub fn start_measurement<M>(&mut self, i2c: &mut I) -> Result<ResultReader<'_, I, M>, E>
+    where
+        M: Measurement,
+    {
+        // Send a command to start the read
+        i2c.write(self.address, &[M::NO_HOLD_COMMAND])?;
+        Ok(ResultReader {
+            address: self.address,
+            _driver: PhantomData,
+            _reading: PhantomData,
+        })
+    }
+
+    /// Starts measuring humidity, for reading the result at a known time
+    ///
+    /// In this mode, the sensor does not stretch the I2C clock. The returned
+    /// `PendingMeasurement` reports the maximum conversion time at `resolution`, which should
+    /// be the resolution configured in the sensor. After that time, call `finish_humidity`
+    /// to read the result.
+    pub fn start_humidity(
+        &mut self,
+        i2c: &mut I,
+        resolution: &Resolution,
+    ) -> Result<PendingMeasurement<Humidity>, E> {
+        self.start_pending(i2c, resolution)
+    }
+
+    /// Starts measuring temperature, for reading the result at a known time
+    ///
+    /// In this mode, the sensor does not stretch the I2C clock. The returned
+    /// `PendingMeasurement` reports the maximum conversion time at `resolution`, which should
+    /// be the resolution configured in the sensor. After that time, call `finish_temperature`
+    /// to read the result.
+    pub fn start_temperature(
+        &mut self,
+        i2c: &mut I,
+        resolution: &Resolution,
+    ) -> Result<PendingMeasurement<Temperature>, E> {
+        self.start_pending(i2c, resolution)
+    }
+
+    /// Reads the result of a humidity measurement that has finished
+    ///
+    /// This function reads the result once, without checking for a NAK. If the measurement
+    /// has not finished, the I2C driver will probably return an error.
+    pub fn finish_humidity(
+        &mut self,
+        pending: PendingMeasurement<Humidity>,
+        i2c: &mut I,
+    ) -> Result<Reading<Humidity>, Error<E>> {
+        self.finish_pending(pending, i2c)
+    }
+
+    /// Reads the result of a temperature measurement that has finished
+    ///
+    /// This function reads the result once, without checking for a NAK. If the measurement
+    /// has not finished, the I2C driver will probably return an error.
+    pub fn finish_temperature(
+        &mut self,
+        pending: PendingMeasurement<Temperature>,
+        i2c: &mut I,
+    ) -> Result<Reading<Temperature>, Error<E>> {
+        self.finish_pending(pending, i2c)
+    }
+
+    /// Sends a no hold master measurement command
+    fn start_pending<M>(
+        &mut self,
+        i2c: &mut I,
+        resolution: &Resolution,
+    ) -> Result<PendingMeasurement<M>, E>
+    where
+        M: Measurement,
+    {
+        i2c.write(self.address, &[M::NO_HOLD_COMMAND])?;
+        Ok(PendingMeasurement {
+            ready_after_ms: conversion_time_ms(resolution, M::TYPE),
+            _reading: PhantomData,
+        })
+    }
+
+    /// Reads the result of a no hold master measurement
+    fn finish_pending<M>(
+        &mut self,
+        _pending: PendingMeasurement<M>,
+        i2c: &mut I,
+    ) -> Result<Reading<M>, Error<E>>
+    where
+        M: Measurement,
+    {
+        let mut buffer = [0u8; 3];
+        i2c.read(self.address, &mut buffer)?;
+        parse_and_check_reading(&buffer)
+    }
+
+    /// Reads the user register and returns its content
+    pub fn read_user_register(&mut self, i2c: &mut I) -> Result<UserRegister, E> {
+        self.read_user_register_raw(i2c).map(UserRegister)
+    }
+
+    /// Reads the user register and returns the current measurement resolution
+    pub fn read_resolution(&mut self, i2c: &mut I) -> Result<Resolution, E> {
+        Ok(self.read_user_register(i2c)?.resolution())
+    }
+
+    /// Reads the user register and returns the maximum conversion times at the current
+    /// resolution, in milliseconds
+    ///
+    /// The first value is the temperature conversion time and the second value is the
+    /// humidity conversion time. See `conversion_time_ms` for details.
+    ///
+    /// If OTP reload is enabled, the sensor returns to the default resolution after the next
+    /// measurement, so this function returns the conversion times at the default resolution.
+    pub fn effective_conversion_times(&mut self, i2c: &mut I) -> Result<(u32, u32), E> {
+        let register = self.read_user_register(i2c)?;
+        let resolution = if register.otp_reload_enabled() {
+            Resolution::Humidity12Temperature14
+        } else {
+            register.resolution()
+        };
+        Ok((
+            conversion_time_ms(&resolution, MeasurementType::Temperature),
+            conversion_time_ms(&resolution, MeasurementType::Humidity),
+        ))
+    }
+
+    /// Reads the user register and returns its content exactly as the sensor sent it,
+    /// including the reserved bits
+    pub fn read_user_register_raw(&mut self, i2c: &mut I) -> Result<u8, E> {
+        let mut register_value = 0u8;
+        i2c.write_read(
+            self.address,
+            &[Command::ReadUser as u8],
+            slice::from_mut(&mut register_value),
+        )?;
+        Ok(register_value)
+    }
+
+    /// Reads the user register from a sensor that sends a CRC byte after the register value
+    ///
+    /// Standard sensors send only the register value, and should be read with
+    /// `read_user_register`. Some compatible sensors send a CRC byte after every value,
+    /// including the user register. This function reads the value and the CRC byte, and
+    /// returns `Error::CrcMismatch` if the CRC does not match.
+    pub fn read_user_register_with_crc(&mut self, i2c: &mut I) -> Result<UserRegister, Error<E>> {
+        let mut buffer = [0u8; 2];
+        i2c.write_read(self.address, &[Command::ReadUser as u8], &mut buffer)?;
+        let mut crc = Crc::new();
+        crc.add(buffer[0]);
+        if crc.value() != buffer[1] {
+            return Err(Error::CrcMismatch {
+                computed: crc.value(),
+                expected: buffer[1],
+            });
+        }
+        Ok(UserRegister(buffer[0]))
+    }
+
+    /// Writes the user register
+    ///
+    /// You should use the `read_user_register` function to get a `UserRegister` object that
+    /// can be modified and then passed to this function.
+    ///
+    /// # Panics
+    ///
+    /// With debug assertions enabled, this function panics if `register.validate()` returns
+    /// an error.
+    pub fn write_user_register(&mut self, i2c: &mut I, register: UserRegister) -> Result<(), E> {
+        debug_assert!(
+            register.validate().is_ok(),
+            "Invalid user register value {:#010b}",
+            register.0
+        );
+        i2c.write(self.address, &[Command::WriteUser as u8, register.0])
+    }
+
+    /// Enables or disables the on-chip heater
+    ///
+    /// This function reads the user register, changes the heater enable bit, and writes the
+    /// register back. The other bits, including the reserved bits, are not changed.
+    pub fn set_heater(&mut self, i2c: &mut I, enabled: bool) -> Result<(), E> {
+        let register = self.read_user_register(i2c)?.with_heater_enabled(enabled);
+        self.write_user_register(i2c, register)
+    }
+
+    /// Reads the user register and returns true if the on-chip heater is enabled
+    pub fn heater_enabled(&mut self, i2c: &mut I) -> Result<bool, E> {
+        Ok(self.read_user_register(i2c)?.heater_enabled())
+    }
+
+    /// Sets the measurement resolution
+    ///
+    /// This function reads the user register, changes the resolution bits, and writes the
+    /// register back. The other bits, including the heater enable bit and the reserved bits,
+    /// are not changed. Use `get_resolution` to read the current resolution.
+    ///
+    /// If one-time programmable memory reload is enabled, the sensor will return to the
+    /// default resolution after the next measurement. `set_persistent_resolution` avoids
+    /// that.
+    pub fn set_resolution(&mut self, i2c: &mut I, resolution: Resolution) -> Result<(), E> {
+        let register = self.read_user_register(i2c)?.with_resolution(resolution);
+        self.write_user_register(i2c, register)
+    }
+
+    /// Reads the user register and returns the current measurement resolution
+    ///
+    /// This is the same as `read_resolution`, but returns the crate's `Error` type like
+    /// the other driver functions.
+    pub fn get_resolution(&mut self, i2c: &mut I) -> Result<Resolution, Error<E>> {
+        Ok(self.read_resolution(i2c)?)
+    }
+
+    /// Sets the measurement resolution so that it is not reset after the next measurement
+    ///
+    /// When one-time programmable memory reload is enabled, the sensor restores its default
+    /// settings (including 12-bit humidity and 14-bit temperature resolution) after each
+    /// measurement. If `resolution` is not the default, this function disables the reload
+    /// in the same register write that sets the resolution. If `resolution` is the default,
+    /// the reload setting is not changed.
+    pub fn set_persistent_resolution(
+        &mut self,
+        i2c: &mut I,
+        resolution: Resolution,
+    ) -> Result<(), E> {
+        let default = resolution == Resolution::Humidity12Temperature14;
+        let mut register = self.read_user_register(i2c)?.with_resolution(resolution);
+        if !default {
+            register.set_otp_reload_enabled(false);
+        }
+        self.write_user_register(i2c, register)
+    }
+
+    /// Resets the sensor, applies a configuration, and reads back the configuration that the
+    /// sensor reports
+    ///
+    /// This function performs a soft reset and waits for it to finish before writing the
+    /// user register. The returned configuration is decoded from the register after writing,
+    /// so it can be compared with the requested configuration to check that every setting
+    /// was applied.
+    pub fn configure_and_read_back<D>(
+        &mut self,
+        i2c: &mut I,
+        delay: &mut D,
+        configuration: Configuration,
+    ) -> Result<Configuration, Error<E>>
+    where
+        D: DelayMs<u32>,
+    {
+        self.soft_reset(i2c)?;
+        delay.delay_ms(SOFT_RESET_TIME_MS);
+
+        let mut register = self.read_user_register(i2c)?;
+        register.set_configuration(&configuration);
+        self.write_user_register(i2c, register)?;
+
+        Ok(self.read_user_register(i2c)?.configuration())
+    }
+
+    /// Reads the 64-bit electronic serial number of the sensor
+    ///
+    /// The serial number is read in two transactions. In each transaction, every CRC byte
+    /// covers all the serial number bytes that came before it in the same transaction. This
+    /// function checks all the CRC bytes and returns `Error::CrcMismatch` if any of them is
+    /// incorrect.
+    ///
+    /// The returned bytes are in order from most significant to least significant.
+    pub fn read_serial_number(&mut self, i2c: &mut I) -> Result<[u8; 8], Error<E>> {
+        // Serial number bytes, most significant first:
+        // SNA_1, SNA_0, SNB_3, SNB_2, SNB_1, SNB_0, SNC_1, SNC_0
+        let mut serial = [0u8; 8];
+
+        // SNB_3, CRC, SNB_2, CRC, SNB_1, CRC, SNB_0, CRC
+        let mut first = [0u8; 8];
+        i2c.write_read(self.address, &SERIAL_NUMBER_FIRST_COMMAND, &mut first)?;
+        check_chained_crc(&first, 1, &mut serial[2..6])?;
+        // SNC_1, SNC_0, CRC, SNA_1, SNA_0, CRC
+        let mut second = [0u8; 6];
+        i2c.write_read(self.address, &SERIAL_NUMBER_SECOND_COMMAND, &mut second)?;
+        let mut snc_sna = [0u8; 4];
+        check_chained_crc(&second, 2, &mut snc_sna)?;
+        serial[6..8].copy_from_slice(&snc_sna[..2]);
+        serial[..2].copy_from_slice(&snc_sna[2..]);
+
+        Ok(serial)
+    }
+
+    /// Reads the firmware revision of the sensor
+    ///
+    /// The returned byte can be converted into a `FirmwareRevision`.
+    pub fn read_firmware_revision(&mut self, i2c: &mut I) -> Result<u8, Error<E>> {
+        let mut revision = 0u8;
+        i2c.write_read(
+            self.address,
+            &FIRMWARE_REVISION_COMMAND,
+            slice::from_mut(&mut revision),
+        )?;
+        Ok(revision)
+    }
+
+    /// Reads the user register `N` times in a row and returns the raw values
+    ///
+    /// This can be used to measure I2C throughput or to detect intermittent corruption.
+    pub fn read_user_register_batch<const N: usize>(&mut self, i2c: &mut I) -> Result<[u8; N], E> {
+        let mut values = [0u8; N];
+        for value in values.iter_mut() {
+            *value = self.read_user_register_raw(i2c)?;
+        }
+        Ok(values)
+    }
+
+    /// Sends an arbitrary command byte and reads a response of `response_len` bytes into
+    /// the beginning of `buf`
+    ///
+    /// This can be used with compatible sensors that use different commands. If
+    /// `response_len` is zero, this function only sends the command.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `buf` is shorter than `response_len`.
+    pub fn read_raw_command(
+        &mut self,
+        i2c: &mut I,
+        command: u8,
+        response_len: usize,
+        buf: &mut [u8],
+    ) -> Result<(), E> {
+        let response = &mut buf[..response_len];
+        if response.is_empty() {
+            i2c.write(self.address, &[command])
+        } else {
+            i2c.write_read(self.address, &[command], response)
+        }
+    }
+
+    /// Reads the user register repeatedly and counts the results
+    ///
+    /// This can be used to check the quality of the I2C wiring. It does not change any
+    /// settings or start any measurements.
+    ///
+    /// This function reads the register exactly `iterations` times. The user register is not
+    /// protected by a CRC, so the first value that is returned by two consecutive reads is
+    /// used as a reference. Successful reads before the reference is confirmed are counted as
+    /// `unconfirmed`. After that, each value is compared with the reference.
+    pub fn bus_health_check(&mut self, i2c: &mut I, iterations: u16) -> BusHealth {
+        let mut reference = None;
+        let mut previous = None;
+        let mut health = BusHealth::default();
+        for _ in 0..iterations {
+            match self.read_user_register(i2c) {
+                Ok(register) => match reference {
+                    Some(reference) if register.0 == reference => health.ok += 1,
+                    Some(_) => health.crc_errors += 1,
+                    None if previous == Some(register.0) => {
+                        reference = Some(register.0);
+                        health.ok += 1;
+                    }
+                    None => {
+                        previous = Some(register.0);
+                        health.unconfirmed += 1;
+                    }
+                },
+                Err(_) => {
+                    previous = None;
+                    health.i2c_errors += 1;
+                }
+            }
+        }
+        health
+    }
+
+    /// Measures the temperature and humidity and checks for signs of a faulty sensor
+    ///
+    /// This function reads the temperature and then the humidity with clock stretching.
+    /// If either reading has an invalid CRC, the result is `Diagnosis::CrcFailure`.
+    /// Otherwise, if either reading is `Reading::ErrorLow`, the result is
+    /// `Diagnosis::OpenCircuitSuspected`, and if either reading is `Reading::ErrorHigh`, the
+    /// result is `Diagnosis::ShortCircuitSuspected`. I2C errors are returned as errors.
+    pub fn self_diagnose(&mut self, i2c: &mut I) -> Result<Diagnosis, Error<E>> {
+        let temperature = diagnose_result(self.read_temperature_blocking(i2c))?;
+        let humidity = diagnose_result(self.read_humidity_blocking(i2c))?;
+        Ok(temperature.max(humidity))
+    }
+
+    /// Measures the temperature and humidity at each resolution
+    ///
+    /// For each resolution, this function writes the user register to select the resolution,
+    /// then measures the temperature and humidity without clock stretching, waiting for the
+    /// maximum conversion time from `conversion_time_ms`. The reports are in the same order
+    /// as the variants of `Resolution`.
+    ///
+    /// After measuring, this function writes back the original user register value, even if
+    /// a measurement failed.
+    pub fn characterize<D>(
+        &mut self,
+        i2c: &mut I,
+        delay: &mut D,
+    ) -> Result<[ResolutionReport; 4], Error<E>>
+    where
+        D: DelayMs<u32>,
+    {
+        let original = self.read_user_register_raw(i2c)?;
+        let result = self.characterize_all(i2c, delay, original);
+        self.write_user_register(i2c, UserRegister(original))?;
+        result
+    }
+
+    /// Measures at each resolution, without restoring the user register
+    fn characterize_all<D>(
+        &mut self,
+        i2c: &mut I,
+        delay: &mut D,
+        original: u8,
+    ) -> Result<[ResolutionReport; 4], Error<E>>
+    where
+        D: DelayMs<u32>,
+    {
+        Ok([
+            self.characterize_resolution(
+                i2c,
+                delay,
+                original,
+                Resolution::Humidity12Temperature14,
+            )?,
+            self.characterize_resolution(i2c, delay, original, Resolution::Humidity8Temperature12)?,
+            self.characterize_resolution(
+                i2c,
+                delay,
+                original,
+                Resolution::Humidity10Temperature13,
+            )?,
+            self.characterize_resolution(
+                i2c,
+                delay,
+                original,
+                Resolution::Humidity11Temperature11,
+            )?,
+        ])
+    }
+
+    /// Selects a resolution and measures the temperature and humidity
+    fn characterize_resolution<D>(
+        &mut self,
+        i2c: &mut I,
+        delay: &mut D,
+        original: u8,
+        resolution: Resolution,
+    ) -> Result<ResolutionReport, Error<E>>
+    where
+        D: DelayMs<u32>,
+    {
+        self.write_user_register(
+            i2c,
+            UserRegister(original).with_resolution(resolution.clone()),
+        )?;
+        let temperature = self.measure_with_delay(i2c, delay, &resolution)?;
+        let humidity = self.measure_with_delay(i2c, delay, &resolution)?;
+        Ok(ResolutionReport {
+            temperature_time_ms: conversion_time_ms(&resolution, MeasurementType::Temperature),
+            humidity_time_ms: conversion_time_ms(&resolution, MeasurementType::Humidity),
+            resolution,
+            temperature,
+            humidity,
+        })
+    }
+}
+
+#[cfg(feature = "hal-02")]
+impl<I, E> Default for Htu2xd<I>
+where
+    I: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    fn default() -> Self {
+        Htu2xd::new()
+    }
+}
+
+/// A measurement that has been started, from `Htu2xd::start_temperature` or
+/// `Htu2xd::start_humidity`
+#[derive(Debug)]
+pub struct PendingMeasurement<M> {
+    /// The maximum conversion time, in milliseconds
+    ready_after_ms: u32,
+    _reading: PhantomData<M>,
+}
+
+impl<M> PendingMeasurement<M> {
+    /// Returns the time after starting the measurement when the result will be ready,
+    /// in milliseconds
+    pub fn ready_after_ms(&self) -> u32 {
+        self.ready_after_ms
+    }
+}
+
+/// A proxy used to read the result of a non-blocking measurement
+pub struct ResultReader<'h, I, M> {
+    /// The I2C address of the sensor
+    #[cfg_attr(not(feature = "hal-02"), allow(dead_code))]
+    address: u8,
+    _driver: PhantomData<&'h mut Htu2xd<I>>,
+    _reading: PhantomData<M>,
+}
+
+#[cfg(feature = "hal-02")]
+impl<'h, I, M> ResultReader<'h, I, M>
+where
+    I: Read,
+    M: Measurement,
+{
+    /// Attempts to read a measurement result from the sensor
+    ///
+    /// is_nak must be a closure that returns true if the provided error is a NAK (negative
+    /// acknowledge) error, or false otherwise.
+    ///
+    /// This function returns `ReadAttempt::Pending` with this reader if the sensor does not
+    /// acknowledge its address. This means that it is still performing the measurement.
+    /// The returned reader should be used again later to try again.
+    ///
+    /// On success, this function returns `ReadAttempt::Done` with the sensor reading.
+    ///
+    /// This function takes the reader by value. When it returns `ReadAttempt::Pending`, the
+    /// reader is returned so that it can be used again:
+    ///
+    /// ```
+    /// use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
+    /// use htu2xd::{Htu2xd, ReadAttempt, Temperature};
+    ///
+    /// fn read_once<I, E>(htu: &mut Htu2xd<I>, i2c: &mut I, is_nak: fn(&E) -> bool)
+    /// where
+    ///     I: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    /// {
+    ///     let reader = htu.start_measurement::<Temperature>(i2c).ok().unwrap();
+    ///     if let Ok(ReadAttempt::Pending(reader)) = reader.read_result(i2c, is_nak) {
+    ///         let _ = reader.read_result(i2c, is_nak);
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// Otherwise, the reader cannot be used again after it has returned a reading or an
+    /// error:
+    ///
+    /// ```compile_fail,E0382
+    /// use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
+    /// use htu2xd::{Htu2xd, Temperature};
+    ///
+    /// fn read_twice<I, E>(htu: &mut Htu2xd<I>, i2c: &mut I, is_nak: fn(&E) -> bool)
+    /// where
+    ///     I: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+    /// {
+    ///     let reader = htu.start_measurement::<Temperature>(i2c).ok().unwrap();
+    ///     let _first = reader.read_result(i2c, is_nak);
+    ///     // Error: reader has been moved
+    ///     let _second = reader.read_result(i2c, is_nak);
+    /// }
+    /// ```
+    pub fn read_result<F>(
+        mut self,
+        i2c: &mut I,
+        is_nak: F,
+    ) -> Result<ReadAttempt<'h, I, M>, Error<I::Error>>
+    where
+        F: FnOnce(&I::Error) -> bool,
+    {
+        match self.poll_result(i2c, is_nak) {
+            Ok(reading) => Ok(ReadAttempt::Done(reading)),
+            Err(nb::Error::WouldBlock) => Ok(ReadAttempt::Pending(self)),
+            Err(nb::Error::Other(e)) => Err(e),
+        }
+    }
+
+    /// Attempts to read a measurement result from the sensor, using `NakError` to detect NAKs
+    ///
+    /// This works like `read_result`, but does not need a closure.
+    pub fn read_result_auto(self, i2c: &mut I) -> Result<ReadAttempt<'h, I, M>, Error<I::Error>>
+    where
+        I::Error: NakError,
+    {
+        self.read_result(i2c, NakError::is_nak)
+    }
+
+    /// Attempts to read a measurement result from the sensor, returning
+    /// `Err(nb::Error::WouldBlock)` if the sensor does not acknowledge its address
+    ///
+    /// After this function returns anything other than `Err(nb::Error::WouldBlock)`, this
+    /// `ResultReader` must not be used again.
+    pub(crate) fn poll_result<F>(
+        &mut self,
+        i2c: &mut I,
+        is_nak: F,
+    ) -> nb::Result<Reading<M>, Error<I::Error>>
+    where
+        F: FnOnce(&I::Error) -> bool,
+    {
+        let mut buffer = [0u8; 3];
+        match i2c.read(self.address, &mut buffer[..]) {
+            Ok(()) => parse_and_check_reading(&buffer).map_err(nb::Error::Other),
+            Err(e) => {
+                if is_nak(&e) {
+                    // Measurement is still in progress, try again later
+                    Err(nb::Error::WouldBlock)
+                } else {
+                    // Some other error, not a NAK
+                    Err(nb::Error::Other(Error::I2c(e)))
+                }
+            }
+        }
+    }
+
+    /// Waits for the measurement to finish and reads the result, with increasing delays
+    /// between attempts
+    ///
+    /// This function waits `config.initial_delay_ms` milliseconds and then attempts to read
+    /// the result. While the sensor does not acknowledge its address (as determined by
+    /// `is_nak`), it multiplies the delay by `config.backoff`, waits, and tries again. After
+    /// `config.max_attempts` unsuccessful attempts, it returns `Error::Timeout`.
+    ///
+    /// Setting `initial_delay_ms` close to the conversion time (see `conversion_time_ms`)
+    /// usually gives the result on the first attempt.
+    pub fn block_with_config<D, F>(
+        mut self,
+        i2c: &mut I,
+        delay: &mut D,
+        config: &PollConfig,
+        mut is_nak: F,
+    ) -> Result<Reading<M>, Error<I::Error>>
+    where
+        D: DelayMs<u32>,
+        F: FnMut(&I::Error) -> bool,
+    {
+        let mut delay_ms = config.initial_delay_ms;
+        for _ in 0..config.max_attempts {
+            delay.delay_ms(delay_ms);
+            match self.poll_result(i2c, &mut is_nak) {
+                Ok(reading) => return Ok(reading),
+                Err(nb::Error::Other(e)) => return Err(e),
+                Err(nb::Error::WouldBlock) => delay_ms = delay_ms.saturating_mul(config.backoff),
+            }
+        }
+        Err(Error::Timeout)
+    }
+}
+
+/// The outcome of an attempt to read a measurement result, from `ResultReader::read_result`
+pub enum ReadAttempt<'h, I, M, R = Reading<M>> {
+    /// The measurement is still in progress, and the reader can be used to try again later
+    Pending(ResultReader<'h, I, M>),
+    /// The measurement has finished
+    Done(R),
+}
+
+/// Settings for `ResultReader::block_with_config`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct PollConfig {
+    /// Time to wait before the first attempt to read the result, in milliseconds
+    pub initial_delay_ms: u32,
+    /// Maximum number of attempts to read the result
+    pub max_attempts: u16,
+    /// Factor that the delay is multiplied by after each unsuccessful attempt
+    ///
+    /// A factor of 1 keeps the delay constant.
+    pub backoff: u32,
+}
+
+#[cfg(feature = "hal-02")]
+impl<'h, I, M> ResultReader<'h, I, M>
+where
+    I: Read,
+    M: Measurement,
+{
+    /// Returns the command byte that started this measurement (0xf3 for temperature or
+    /// 0xf5 for humidity)
+    pub fn command(&self) -> u8 {
+        M::NO_HOLD_COMMAND
+    }
+
+    /// Returns the type of measurement that this reader reads
+    pub fn kind(&self) -> MeasurementType {
+        M::TYPE
+    }
+
+    /// Converts this reader into an iterator that attempts to read the result each time
+    /// `next()` is called
+    ///
+    /// is_nak must be a closure that returns true if the provided error is a NAK (negative
+    /// acknowledge) error, or false otherwise. The iterator keeps the closure and uses it for
+    /// every attempt.
+    ///
+    /// Each call to `next()` attempts to read the result once. If the sensor does not
+    /// acknowledge its address (the measurement is still in progress), the iterator yields
+    /// `Poll::Pending`. Otherwise, it yields `Poll::Ready` with the reading or error. After
+    /// yielding `Poll::Ready`, the iterator returns `None`.
+    pub fn into_poll_iter<'i, F>(self, i2c: &'i mut I, is_nak: F) -> PollIter<'h, 'i, I, M, F>
+    where
+        F: Fn(&I::Error) -> bool,
+    {
+        PollIter {
+            reader: Some(self),
+            i2c,
+            is_nak,
+        }
+    }
+}
+
+/// An iterator that polls for the result of a non-blocking measurement
+///
+/// This is created by `ResultReader::into_poll_iter`.
+#[cfg(feature = "hal-02")]
+pub struct PollIter<'h, 'i, I, M, F> {
+    /// The reader, or None if the result has already been read
+    reader: Option<ResultReader<'h, I, M>>,
+    i2c: &'i mut I,
+    is_nak: F,
+}
+
+#[cfg(feature = "hal-02")]
+impl<'h, 'i, I, M, F> Iterator for PollIter<'h, 'i, I, M, F>
+where
+    I: Read,
+    M: Measurement,
+    F: Fn(&I::Error) -> bool,
+{
+    type Item = Poll<Result<Reading<M>, Error<I::Error>>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let reader = self.reader.as_mut()?;
+        let result = match reader.poll_result(self.i2c, &self.is_nak) {
+            Err(nb::Error::WouldBlock) => return Some(Poll::Pending),
+            Ok(reading) => Ok(reading),
+            Err(nb::Error::Other(e)) => Err(e),
+        };
+        // Done, the reader must not be used again
+        self.reader = None;
+        Some(Poll::Ready(result))
+    }
+}
+
+#[cfg(feature = "hal-02")]
+impl<'h, I> ResultReader<'h, I, Humidity>
+where
+    I: Read,
+{
+    /// Attempts to read a humidity measurement result from the sensor and compensates it for
+    /// the temperature
+    ///
+    /// This function works like `read_result`, but converts a successful reading into percent
+    /// relative humidity using `Humidity::compensate`.
+    pub fn read_result_compensated<F>(
+        self,
+        i2c: &mut I,
+        temperature: &Temperature,
+        is_nak: F,
+    ) -> Result<ReadAttempt<'h, I, Humidity, Reading<f32>>, Error<I::Error>>
+    where
+        F: FnOnce(&I::Error) -> bool,
+    {
+        let reading = match self.read_result(i2c, is_nak)? {
+            ReadAttempt::Pending(reader) => return Ok(ReadAttempt::Pending(reader)),
+            ReadAttempt::Done(reading) => reading,
+        };
+        Ok(ReadAttempt::Done(match reading {
+            Reading::Ok(humidity) => Reading::Ok(humidity.compensate(temperature)),
+            Reading::ErrorLow => Reading::ErrorLow,
+            Reading::ErrorHigh => Reading::ErrorHigh,
+        }))
+    }
+}
+
+/// Checks a sequence of data bytes with CRC bytes after every `group_size` data bytes, where
+/// each CRC covers all the data bytes before it
+///
+/// The data bytes are copied into `data`. This function returns `Error::CrcMismatch` for the
+/// first CRC that is incorrect.
+#[cfg(feature = "hal-02")]
+fn check_chained_crc<E>(frame: &[u8], group_size: usize, data: &mut [u8]) -> Result<(), Error<E>> {
+    let mut crc = Crc::new();
+    let mut data_out = data.iter_mut();
+    for group in frame.chunks(group_size + 1) {
+        let (group_data, group_crc) = group.split_at(group_size);
+        for (&byte, out) in group_data.iter().zip(&mut data_out) {
+            crc.add(byte);
+            *out = byte;
+        }
+        if group_crc != [crc.value()] {
+            return Err(Error::CrcMismatch {
+                computed: crc.value(),
+                expected: group_crc[0],
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Checks the CRC of a 3-byte temperature or humidity reading and parses it as a `Reading` object
+#[cfg(any(feature = "hal-02", feature = "hal-1", feature = "async"))]
+fn parse_and_check_reading<M, E>(bytes: &[u8; 3]) -> Result<Reading<M>, Error<E>>
+where
+    M: Measurement,
+{
+    let mut crc = Crc::new();
+    parse_reading_with_crc(bytes, &mut crc).map_err(|_| {
+        // Calculate the CRC of the data bytes only, for diagnostics
+        crc.reset();
+        crc.add_all(&bytes[..2]);
+        Error::Crc {
+            received: *bytes,
+            computed: crc.value(),
+        }
+    })
+}
+
+/// Checks the CRC of a 3-byte temperature or humidity frame from the sensor and parses it as
+/// a `Reading` object, using a CRC calculator provided by the caller
+///
+/// This function resets `crc` before using it, so the same calculator can be reused for
+/// many frames without creating a new one each time.
+pub fn parse_reading_with_crc<M>(bytes: &[u8; 3], crc: &mut Crc) -> Result<Reading<M>, CrcError>
+where
+    M: Measurement,
+{
+    // Check CRC
+    crc.reset();
+    crc.add_all(bytes);
+    if crc.value() != 0 {
+        return Err(CrcError::Mismatch);
+    }
+
+    Ok(Reading::from_raw(raw_from_frame(bytes)))
+}
+
+/// Assembles the 16-bit raw value from a 3-byte reading frame
+///
+/// The sensor sends the most significant byte first, followed by the least significant byte
+/// and the CRC byte. The result does not depend on the byte order of the host. The status
+/// bits (the two least significant bits) are included, and the CRC is not checked.
+pub fn raw_from_frame(bytes: &[u8; 3]) -> u16 {
+    (u16::from(bytes[0]) << 8) | u16::from(bytes[1])
+}
+
+/// Results of a bus health check
+#[derive(Debug, Clone, Default)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct BusHealth {
+    /// Number of reads that completed successfully
+    pub ok: u16,
+    /// Number of reads that returned a value different from the confirmed reference value
+    ///
+    /// These are value mismatches (the user register has no CRC).
+    pub crc_errors: u16,
+    /// Number of reads where the I2C driver returned an error
+    pub i2c_errors: u16,
+    /// Number of successful reads before a reference value was confirmed
+    ///
+    /// If no two consecutive reads returned the same value, every successful read is
+    /// counted here.
+    pub unconfirmed: u16,
+}
+
+/// Classifies the result of reading a temperature or humidity for `Htu2xd::self_diagnose`
+#[cfg(feature = "hal-02")]
+fn diagnose_result<M, E>(result: Result<Reading<M>, Error<E>>) -> Result<Diagnosis, Error<E>> {
+    match result {
+        Ok(Reading::Ok(_)) => Ok(Diagnosis::Ok),
+        Ok(Reading::ErrorLow) => Ok(Diagnosis::OpenCircuitSuspected),
+        Ok(Reading::ErrorHigh) => Ok(Diagnosis::ShortCircuitSuspected),
+        Err(Error::Crc { .. }) | Err(Error::CrcMismatch { .. }) => Ok(Diagnosis::CrcFailure),
+        Err(e) => Err(e),
+    }
+}
+
+/// The health of a sensor, from `Htu2xd::self_diagnose`
+///
+/// The variants are ordered from least to most severe.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Diagnosis {
+    /// Both readings were valid
+    Ok,
+    /// A reading was `Reading::ErrorLow`, which usually means an open circuit
+    OpenCircuitSuspected,
+    /// A reading was `Reading::ErrorHigh`, which usually means a short circuit
+    ShortCircuitSuspected,
+    /// A reading had an invalid CRC
+    CrcFailure,
+}
+
+/// A sensor firmware revision, from `Htu2xd::read_firmware_revision`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FirmwareRevision {
+    /// Version 1.0 (revision byte 0xff)
+    V1_0,
+    /// Version 2.0 (revision byte 0x20)
+    V2_0,
+    /// A revision byte that does not match any documented version
+    Unknown(u8),
+}
+
+impl From<u8> for FirmwareRevision {
+    fn from(revision: u8) -> Self {
+        match revision {
+            0xff => FirmwareRevision::V1_0,
+            0x20 => FirmwareRevision::V2_0,
+            other => FirmwareRevision::Unknown(other),
+        }
+    }
+}
+
+/// Measurements at one resolution, from `Htu2xd::characterize`
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ResolutionReport {
+    /// The resolution used for the measurements
+    pub resolution: Resolution,
+    /// The temperature reading
+    pub temperature: Reading<Temperature>,
+    /// The humidity reading
+    pub humidity: Reading<Humidity>,
+    /// The time waited for the temperature measurement, in milliseconds
+    pub temperature_time_ms: u32,
+    /// The time waited for the humidity measurement, in milliseconds
+    pub humidity_time_ms: u32,
+}
+
+/// An I2C, CRC, or other error
+#[derive(Debug)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Error<E> {
+    /// The I2C driver returned an error
+    I2c(E),
+    /// A temperature or humidity reading was received from the sensor with an invalid CRC
+    /// checksum
+    ///
+    /// `received` contains the two data bytes and the CRC byte that the sensor sent, and
+    /// `computed` is the CRC calculated from the received data bytes. Comparing `computed`
+    /// with `received[2]` can help diagnose wiring problems.
+    Crc {
+        /// The two data bytes and the CRC byte received from the sensor
+        received: [u8; 3],
+        /// The CRC calculated from the received data bytes
+        computed: u8,
+    },
+    /// A user register value or serial number was received from the sensor with an invalid
+    /// CRC checksum
+    ///
+    /// `computed` is the CRC calculated from the received data bytes, and `expected` is the
+    /// CRC byte that the sensor sent. For the serial number, `computed` covers all the data
+    /// bytes received before the incorrect CRC byte in the same transaction.
+    CrcMismatch {
+        /// The CRC calculated from the received data bytes
+        computed: u8,
+        /// The CRC byte received from the sensor
+        expected: u8,
+    },
+    /// The sensor returned an off-scale reading
+    OffScale(Bound),
+    /// After a soft reset, the sensor did not respond or did not have its reset settings
+    ResetFailed,
+    /// A measurement could not be started because another measurement is in progress
+    MeasurementInProgress,
+    /// A measurement result was requested, but no measurement of that type is in progress
+    NoMeasurementPending,
+    /// The sensor did not finish a measurement in time
+    ///
+    /// This happens when the I2C driver times out while the sensor is stretching the clock,
+    /// or when a measurement result is not ready after the maximum number of attempts.
+    Timeout,
+}
+
+/// The end of the measurement range that an off-scale reading crossed
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Bound {
+    /// The reading was very low, or the sensor has an open circuit
+    Low,
+    /// The reading was very high, or the sensor has a short circuit
+    High,
+}
+
+impl<E> From<E> for Error<E> {
+    fn from(inner: E) -> Self {
+        Error::I2c(inner)
+    }
+}
+
+impl<E> fmt::Display for Error<E>
+where
+    E: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::I2c(inner) => write!(f, "I2C error: {}", inner),
+            Error::Crc { received, computed } => write!(
+                f,
+                "invalid CRC: computed {:#04x}, received {:02x?}",
+                computed, received
+            ),
+            Error::CrcMismatch { computed, expected } => write!(
+                f,
+                "invalid CRC: computed {:#04x}, expected {:#04x}",
+                computed, expected
+            ),
+            Error::OffScale(Bound::Low) => f.write_str("reading off-scale low"),
+            Error::OffScale(Bound::High) => f.write_str("reading off-scale high"),
+            Error::ResetFailed => f.write_str("sensor did not reset"),
+            Error::MeasurementInProgress => f.write_str("another measurement is in progress"),
+            Error::NoMeasurementPending => f.write_str("no measurement is in progress"),
+            Error::Timeout => f.write_str("measurement timed out"),
+        }
+    }
+}
+
+/// This implementation requires the `std` feature.
+#[cfg(feature = "std")]
+impl<E> std::error::Error for Error<E>
+where
+    E: std::error::Error + 'static,
+{
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::I2c(inner) => Some(inner),
+            _ => None,
+        }
+    }
+}
+
+/// A temperature reading
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Temperature(u16);
+
+impl Temperature {
+    /// Creates a temperature value from degrees Celsius
+    ///
+    /// The temperature is converted into the nearest raw value that the sensor can report,
+    /// from about -46.85 °C to about 128.86 °C. Temperatures outside that range are clamped
+    /// to it.
+    ///
+    /// This function uses single-precision floating-point operations.
+    pub fn from_degrees_celsius(degrees: f32) -> Self {
+        let steps = (degrees + 46.85_f32) * (65536.0_f32 / 175.72_f32) / 4.0;
+        Temperature(raw_from_steps(steps))
+    }
+
+    /// Returns the temperature reading exactly as read from the sensor, with the status bits
+    /// cleared
+    pub fn as_raw(&self) -> u16 {
+        self.0
+    }
+
+    /// Converts the temperature reading into degrees Celsius
+    ///
+    /// This function uses single-precision floating-point operations.
+    pub fn as_degrees_celsius(&self) -> f32 {
+        -46.85_f32 + 175.72_f32 / 65536.0_f32 * f32::from(self.0)
+    }
+
+    /// Converts the temperature reading into thousandths of a degree Celsius
+    ///
+    /// This function uses only integer operations.
+    pub fn as_millidegrees_celsius(&self) -> i32 {
+        (((175720 * i64::from(self.0) + 0x8000) >> 16) - 46850) as i32
+    }
+
+    /// Converts the temperature reading into degrees Fahrenheit
+    ///
+    /// This function uses single-precision floating-point operations.
+    pub fn as_degrees_fahrenheit(&self) -> f32 {
+        // The Celsius formula with both coefficients multiplied by 9/5 and 32 added
+        -52.33_f32 + 316.296_f32 / 65536.0_f32 * f32::from(self.0)
+    }
+
+    /// Converts the temperature reading into kelvins
+    ///
+    /// This function uses single-precision floating-point operations.
+    pub fn as_kelvin(&self) -> f32 {
+        // The Celsius formula with 273.15 added
+        226.3_f32 + 175.72_f32 / 65536.0_f32 * f32::from(self.0)
+    }
+
+    /// Converts the temperature reading into degrees Celsius after applying a calibration
+    /// correction
+    ///
+    /// The correction is interpolated from `table` at the raw value of this reading and added
+    /// to the raw value. The corrected raw value is then converted like `as_degrees_celsius`.
+    pub fn corrected<const N: usize>(&self, table: &CorrectionTable<N>) -> f32 {
+        let raw = f32::from(self.0) + table.correction_at(self.0);
+        -46.85_f32 + 175.72_f32 / 65536.0_f32 * raw
+    }
+
+    /// Converts the temperature reading into degrees Celsius and subtracts the temperature rise
+    /// caused by the on-chip heater
+    ///
+    /// When the heater is enabled, it raises the sensor temperature by about 0.5 to 1.5 °C,
+    /// depending on the supply voltage and the surroundings. `heater_rise_c` should be the
+    /// rise measured for the specific installation, or about 1.0 if it is unknown.
+    ///
+    /// This function uses single-precision floating-point operations.
+    pub fn compensate_heater(&self, heater_rise_c: f32) -> f32 {
+        self.as_degrees_celsius() - heater_rise_c
+    }
+
+    /// Converts the temperature reading into degrees Celsius, rounded to the precision of a
+    /// measurement resolution
+    ///
+    /// At resolutions lower than 14 bits, the least significant bits of the raw value do not
+    /// carry any information. This function rounds the raw value to the nearest value that
+    /// the resolution can represent before converting it.
+    ///
+    /// This function uses single-precision floating-point operations.
+    pub fn as_degrees_celsius_at(&self, resolution: &Resolution) -> f32 {
+        let step = 1u32 << (16 - resolution.temperature_bits());
+        let max = 0x1_0000 - step;
+        let rounded = ((u32::from(self.0) + step / 2) & !(step - 1)).min(max);
+        Temperature(rounded as u16).as_degrees_celsius()
+    }
+
+    /// Returns the reading as a fraction of the full scale at a measurement resolution
+    ///
+    /// This keeps only the most significant bits of `as_raw()` that the resolution measures,
+    /// and divides them by their maximum value. The result ranges from 0.0 to 1.0 and does
+    /// not depend on the conversion to degrees Celsius.
+    pub fn as_normalized(&self, resolution: &Resolution) -> f32 {
+        normalize(self.0, resolution.temperature_bits())
+    }
+
+    /// Returns the difference between this temperature and an earlier temperature, in degrees
+    ///
+    /// The difference is calculated from the raw values and then converted, so it is exact
+    /// apart from the final floating-point multiplication. The result is positive if this
+    /// temperature is higher.
+    pub fn delta(&self, earlier: &Temperature) -> f32 {
+        let difference = i32::from(self.0) - i32::from(earlier.0);
+        175.72_f32 / 65536.0_f32 * difference as f32
+    }
+
+    /// Returns true if the raw values of this reading and another reading differ by no more
+    /// than `tol_raw`
+    ///
+    /// One raw count is about 0.0027 degrees Celsius.
+    pub fn approx_eq(&self, other: &Temperature, tol_raw: u16) -> bool {
+        self.0.abs_diff(other.0) <= tol_raw
+    }
+}
+
+/// Rounds a number of 4-count steps to the nearest raw value with the status bits cleared,
+/// clamping it to the range of raw values
+fn raw_from_steps(steps: f32) -> u16 {
+    ((steps + 0.5).clamp(0.0, 16383.0) as u16) << 2
+}
+
+/// Scales the most significant `bits` bits of a raw value to the range 0.0 to 1.0
+fn normalize(raw: u16, bits: u32) -> f32 {
+    let value = raw >> (16 - bits);
+    let max = (1u32 << bits) - 1;
+    f32::from(value) / max as f32
+}
+
+/// A humidity reading
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Humidity(u16);
+
+impl Humidity {
+    /// Creates a humidity value from percent relative humidity
+    ///
+    /// The humidity is converted into the nearest raw value that the sensor can report,
+    /// from -6% to about 118.99%. Values outside that range are clamped to it.
+    ///
+    /// This function uses single-precision floating-point operations.
+    pub fn from_percent(percent: f32) -> Self {
+        let steps = (percent + 6.0_f32) * (65536.0_f32 / 125.0_f32) / 4.0;
+        Humidity(raw_from_steps(steps))
+    }
+
+    /// Returns the humidity reading exactly as read from the sensor, with the status bits cleared
+    pub fn as_raw(&self) -> u16 {
+        self.0
+    }
+
+    /// Converts the humidity reading into percent relative humidity (0.0 = 0%, 100.0 = 100%)
+    /// and clamps it to the 0%-100% range
+    ///
+    /// This function uses single-precision floating-point operations.
+    pub fn as_percent_relative(&self) -> f32 {
+        self.as_percent_relative_unclamped().clamp(0.0, 100.0)
+    }
+
+    /// Converts the humidity reading into percent relative humidity (0.0 = 0%, 100.0 = 100%)
+    /// without clamping it
+    ///
+    /// The result can be anywhere from -6% to about 119%. Values slightly above 100% are
+    /// physically meaningful and can indicate condensation on the sensor. Values far outside
+    /// the 0%-100% range can indicate sensor drift or damage.
+    ///
+    /// This function uses single-precision floating-point operations.
+    pub fn as_percent_relative_unclamped(&self) -> f32 {
+        -6.0_f32 + 125.0_f32 / 65536.0_f32 * f32::from(self.0)
+    }
+
+    /// Converts the humidity reading into tenths of a percent relative humidity (0 = 0%,
+    /// 1000 = 100%) and clamps it to the 0%-100% range
+    ///
+    /// This function uses only integer operations.
+    pub fn as_permille(&self) -> u16 {
+        self.as_permille_unclamped().clamp(0, 1000) as u16
+    }
+
+    /// Converts the humidity reading into tenths of a percent relative humidity (0 = 0%,
+    /// 1000 = 100%) without clamping it
+    ///
+    /// The result can be anywhere from -60 to about 1190. See `as_percent_relative_unclamped`
+    /// for the meaning of values outside the 0%-100% range.
+    ///
+    /// This function uses only integer operations.
+    pub fn as_permille_unclamped(&self) -> i32 {
+        ((1250 * i32::from(self.0) + 0x8000) >> 16) - 60
+    }
+
+    /// Returns the reading as a fraction of the full scale at a measurement resolution
+    ///
+    /// This keeps only the most significant bits of `as_raw()` that the resolution measures,
+    /// and divides them by their maximum value. The result ranges from 0.0 to 1.0 and does
+    /// not depend on the conversion to percent relative humidity.
+    pub fn as_normalized(&self, resolution: &Resolution) -> f32 {
+        normalize(self.0, resolution.humidity_bits())
+    }
+
+    /// Converts the humidity reading into percent relative humidity after applying a
+    /// calibration correction
+    ///
+    /// The correction is interpolated from `table` at the raw value of this reading and added
+    /// to the raw value. The corrected raw value is then converted like
+    /// `as_percent_relative_unclamped`, without clamping.
+    pub fn corrected<const N: usize>(&self, table: &CorrectionTable<N>) -> f32 {
+        let raw = f32::from(self.0) + table.correction_at(self.0);
+        -6.0_f32 + 125.0_f32 / 65536.0_f32 * raw
+    }
+
+    /// Converts the humidity reading into percent relative humidity, like
+    /// `as_percent_relative`, and wraps it in a type that keeps track of the unit
+    pub fn relative_humidity(&self) -> RelativeHumidity {
+        RelativeHumidity(self.as_percent_relative())
+    }
+
+    /// Converts this reading into percent relative humidity and compensates it for the
+    /// temperature
+    ///
+    /// This function uses the compensation coefficient from the datasheet, -0.15 %RH/°C.
+    /// It is equivalent to `compensate_with(temperature, -0.15)`.
+    pub fn compensate(&self, temperature: &Temperature) -> f32 {
+        self.compensate_with(temperature, HUMIDITY_TEMPERATURE_COEFFICIENT)
+    }
+
+    /// Converts this reading into percent relative humidity and compensates it for the
+    /// temperature using a custom coefficient
+    ///
+    /// The compensated humidity is `RH + (25 - T) * coeff_pct_per_c`, where `RH` is the
+    /// uncompensated humidity in percent and `T` is the temperature in degrees Celsius.
+    ///
+    /// This function uses single-precision floating-point operations.
+    pub fn compensate_with(&self, temperature: &Temperature, coeff_pct_per_c: f32) -> f32 {
+        self.as_percent_relative() + (25.0_f32 - temperature.as_degrees_celsius()) * coeff_pct_per_c
+    }
+
+    /// Returns the difference between this humidity and an earlier humidity, in percent
+    /// relative humidity
+    ///
+    /// The difference is calculated from the raw values and then converted, so it is exact
+    /// apart from the final floating-point multiplication. The result is not clamped, and is
+    /// positive if this humidity is higher.
+    pub fn delta(&self, earlier: &Humidity) -> f32 {
+        let difference = i32::from(self.0) - i32::from(earlier.0);
+        125.0_f32 / 65536.0_f32 * difference as f32
+    }
+
+    /// Returns true if the raw values of this reading and another reading differ by no more
+    /// than `tol_raw`
+    ///
+    /// One raw count is about 0.0019 percent relative humidity.
+    pub fn approx_eq(&self, other: &Humidity, tol_raw: u16) -> bool {
+        self.0.abs_diff(other.0) <= tol_raw
+    }
+}
+
+/// A relative humidity value in percent (0.0 = 0%, 100.0 = 100%)
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct RelativeHumidity(f32);
+
+impl RelativeHumidity {
+    /// Returns the relative humidity in percent
+    pub fn value(&self) -> f32 {
+        self.0
+    }
+}
+
+impl fmt::Display for RelativeHumidity {
+    /// Formats the humidity with one decimal place, like `44.8 %`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.1} %", self.0)
+    }
+}
+
+impl fmt::Display for Temperature {
+    /// Formats the temperature in degrees Celsius with one decimal place, like `7.0 °C`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.1} °C", self.as_degrees_celsius())
+    }
+}
+
+impl fmt::Display for Humidity {
+    /// Formats the relative humidity in percent with one decimal place, like `44.8 %RH`
+    ///
+    /// The humidity is clamped to the 0%-100% range like `as_percent_relative`.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:.1} %RH", self.as_percent_relative())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Temperature {
+    /// Deserializes the raw value and clears the status bits, like a reading from the sensor
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        u16::deserialize(deserializer).map(|raw| Temperature(raw & 0xfffc))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Humidity {
+    /// Deserializes the raw value and clears the status bits, like a reading from the sensor
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        u16::deserialize(deserializer).map(|raw| Humidity(raw & 0xfffc))
+    }
+}
+
+impl<R> fmt::Display for Reading<R>
+where
+    R: fmt::Display,
+{
+    /// Formats a valid reading like its value, and an off-scale reading as `<low>` or `<high>`
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Reading::Ok(value) => value.fmt(f),
+            Reading::ErrorLow => f.write_str("<low>"),
+            Reading::ErrorHigh => f.write_str("<high>"),
+        }
+    }
+}
+
+/// The quantity that a measurement represents
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum MeasurementType {
+    /// Temperature
+    Temperature,
+    /// Relative humidity
+    Humidity,
+}
+
+/// A type of measurement that the sensor can take (temperature or humidity)
+pub trait Measurement: SealedMeasurement {}
+impl SealedMeasurement for Temperature {
+    const TYPE: MeasurementType = MeasurementType::Temperature;
+    const NO_HOLD_COMMAND: u8 = Command::Temperature as u8;
+    fn from_raw(raw: u16) -> Self {
+        Temperature(raw)
+    }
+    fn raw(&self) -> u16 {
+        self.0
+    }
+}
+impl Measurement for Temperature {}
+impl SealedMeasurement for Humidity {
+    const TYPE: MeasurementType = MeasurementType::Humidity;
+    const NO_HOLD_COMMAND: u8 = Command::Humidity as u8;
+    fn from_raw(raw: u16) -> Self {
+        Humidity(raw)
+    }
+    fn raw(&self) -> u16 {
+        self.0
+    }
+}
+impl Measurement for Humidity {}
+
+/// Information about a temperature or humidity reading
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Reading<R> {
     /// The reading was completed normally
     Ok(R),
@@ -379,10 +2313,107 @@ pub enum Reading<R> {
     ErrorHigh,
 }
 
+impl<R> Reading<R> {
+    /// Returns true if this reading was completed normally
+    pub fn is_ok(&self) -> bool {
+        matches!(self, Reading::Ok(_))
+    }
+    /// Returns true if this reading was very low or the sensor has an open circuit
+    pub fn is_error_low(&self) -> bool {
+        matches!(self, Reading::ErrorLow)
+    }
+    /// Returns true if this reading was very high or the sensor has a short circuit
+    pub fn is_error_high(&self) -> bool {
+        matches!(self, Reading::ErrorHigh)
+    }
+
+    /// Returns true if this reading indicates that the sensor likely has an open circuit
+    ///
+    /// The sensor reports a raw value of 0x0000 (`ErrorLow`) when its sensing element has an
+    /// open circuit. This value is far enough below the normal range that it is more likely
+    /// to come from a fault than from a real measurement.
+    pub fn is_likely_open_circuit(&self) -> bool {
+        self.is_error_low()
+    }
+    /// Returns true if this reading indicates that the sensor likely has a short circuit
+    ///
+    /// The sensor reports a raw value of 0xffff (`ErrorHigh`) when its sensing element has a
+    /// short circuit. This value is far enough above the normal range that it is more likely
+    /// to come from a fault than from a real measurement.
+    pub fn is_likely_short_circuit(&self) -> bool {
+        self.is_error_high()
+    }
+
+    /// Converts this reading into a `Result`, with an error that indicates which end of the
+    /// range an off-scale reading crossed
+    ///
+    /// Off-scale readings can also indicate an open or short circuit in the sensor. See
+    /// `is_likely_open_circuit`, `is_likely_short_circuit`, and `diagnose`.
+    pub fn into_result(self) -> Result<R, ReadingError> {
+        match self {
+            Reading::Ok(value) => Ok(value),
+            Reading::ErrorLow => Err(ReadingError::OffScaleLow),
+            Reading::ErrorHigh => Err(ReadingError::OffScaleHigh),
+        }
+    }
+
+    /// Converts this reading into a number using a conversion function, or returns NaN if
+    /// the reading is off-scale
+    ///
+    /// Example: `reading.into_f32_or_nan(|t| t.as_degrees_celsius())`
+    pub fn into_f32_or_nan<F>(self, convert: F) -> f32
+    where
+        F: FnOnce(R) -> f32,
+    {
+        match self {
+            Reading::Ok(value) => convert(value),
+            Reading::ErrorLow | Reading::ErrorHigh => f32::NAN,
+        }
+    }
+}
+
 impl<R> Reading<R>
 where
     R: Measurement,
 {
+    /// Converts this reading into a measurement, or an `Error::OffScale` if it is off-scale
+    #[cfg(any(feature = "hal-02", feature = "async"))]
+    fn into_value<E>(self) -> Result<R, Error<E>> {
+        match self {
+            Reading::Ok(value) => Ok(value),
+            Reading::ErrorLow => Err(Error::OffScale(Bound::Low)),
+            Reading::ErrorHigh => Err(Error::OffScale(Bound::High)),
+        }
+    }
+
+    /// Classifies this reading as normal, a likely sensor fault, or a likely extreme value
+    ///
+    /// `previous` should be the most recent normal reading of the same quantity, if one is
+    /// available. An off-scale reading is classified as an extreme value if the previous
+    /// reading was within 1/16 of the full scale of the same end of the range, because the
+    /// quantity could have changed that far since then. Otherwise, `ErrorLow` is classified as
+    /// a likely open circuit and `ErrorHigh` as a likely short circuit.
+    pub fn diagnose(&self, previous: Option<&R>) -> ReadingDiagnosis {
+        /// Raw values this close to either end of the range are considered near that end
+        const NEAR_END: u16 = 0x1000;
+        let previous_raw = previous.map(SealedMeasurement::raw);
+        match self {
+            Reading::Ok(_) => ReadingDiagnosis::Ok,
+            Reading::ErrorLow => match previous_raw {
+                Some(raw) if raw < NEAR_END => ReadingDiagnosis::LikelyExtremeLow,
+                _ => ReadingDiagnosis::LikelyOpenCircuit,
+            },
+            Reading::ErrorHigh => match previous_raw {
+                Some(raw) if raw > !NEAR_END => ReadingDiagnosis::LikelyExtremeHigh,
+                _ => ReadingDiagnosis::LikelyShortCircuit,
+            },
+        }
+    }
+
+    /// Converts a raw value, including the status bits, into a reading
+    ///
+    /// The error values are checked before the status bits are cleared, so a genuine reading
+    /// with a status bit set (like 0x0002) is not mistaken for `ErrorLow`.
     fn from_raw(raw: u16) -> Self {
         match raw {
             0x0000 => Reading::ErrorLow,
@@ -395,7 +2426,60 @@ where
     }
 }
 
+impl<M> TryFrom<&[u8]> for Reading<M>
+where
+    M: Measurement,
+{
+    type Error = FrameError;
+
+    /// Checks the length and CRC of a temperature or humidity frame from the sensor and
+    /// parses it
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        let frame: &[u8; 3] = bytes
+            .try_into()
+            .map_err(|_| FrameError::Length(bytes.len()))?;
+        parse_reading_with_crc(frame, &mut Crc::new()).map_err(|_| FrameError::Crc)
+    }
+}
+
+/// An error from parsing a slice of bytes as a `Reading`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum FrameError {
+    /// The slice had this length instead of 3 bytes
+    Length(usize),
+    /// The CRC byte did not match the data bytes
+    Crc,
+}
+
+/// An off-scale reading, from `Reading::into_result`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ReadingError {
+    /// The reading was very low, or the sensor has an open circuit
+    OffScaleLow,
+    /// The reading was very high, or the sensor has a short circuit
+    OffScaleHigh,
+}
+
+/// A classification of a reading, from `Reading::diagnose`
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ReadingDiagnosis {
+    /// The reading was completed normally
+    Ok,
+    /// The reading was off-scale low, probably because the sensor has an open circuit
+    LikelyOpenCircuit,
+    /// The reading was off-scale high, probably because the sensor has a short circuit
+    LikelyShortCircuit,
+    /// The reading was off-scale low, probably because the measured value was very low
+    LikelyExtremeLow,
+    /// The reading was off-scale high, probably because the measured value was very high
+    LikelyExtremeHigh,
+}
+
 /// Commands to read and write things
+#[cfg_attr(not(feature = "hal-02"), allow(dead_code))]
 enum Command {
     TemperatureHoldMaster = 0xe3,
     Temperature = 0xf3,
@@ -404,4 +2488,264 @@ enum Command {
     WriteUser = 0xe6,
     ReadUser = 0xe7,
     SoftReset = 0xfe,
+    TemperatureFromHumidity = 0xe0,
+}
+
+#[cfg(test)]
+mod display_test {
+    extern crate std;
+
+    use super::{Humidity, Reading, Temperature};
+    use std::string::ToString;
+
+    #[test]
+    fn display() {
+        assert_eq!(Temperature(0x4e84).to_string(), "7.0 °C");
+        assert_eq!(Humidity(0x6838).to_string(), "44.9 %RH");
+        assert_eq!(Reading::Ok(Temperature(0x6000)).to_string(), "19.0 °C");
+        assert_eq!(Reading::<Humidity>::ErrorLow.to_string(), "<low>");
+        assert_eq!(Reading::<Humidity>::ErrorHigh.to_string(), "<high>");
+    }
+}
+
+#[cfg(all(test, feature = "serde"))]
+mod serde_test {
+    use super::{Humidity, Reading, Resolution, SupplyVoltage, Temperature};
+
+    #[test]
+    fn round_trip() {
+        let temperature = Reading::Ok(Temperature(0x4e84));
+        let json = serde_json::to_string(&temperature).unwrap();
+        assert_eq!(json, r#"{"Ok":20100}"#);
+        let decoded: Reading<Temperature> = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.into_result().unwrap().as_raw(), 0x4e84);
+
+        let json = serde_json::to_string(&Humidity(0x6838)).unwrap();
+        let decoded: Humidity = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded.as_raw(), 0x6838);
+
+        // The status bits are cleared
+        let decoded: Temperature = serde_json::from_str("20103").unwrap();
+        assert_eq!(decoded.as_raw(), 0x4e84);
+        let decoded: Humidity = serde_json::from_str("26682").unwrap();
+        assert_eq!(decoded.as_raw(), 0x6838);
+
+        let json = serde_json::to_string(&Reading::<Humidity>::ErrorHigh).unwrap();
+        let decoded: Reading<Humidity> = serde_json::from_str(&json).unwrap();
+        assert!(decoded.is_error_high());
+
+        let json = serde_json::to_string(&Resolution::Humidity8Temperature12).unwrap();
+        let decoded: Resolution = serde_json::from_str(&json).unwrap();
+        assert_eq!(decoded, Resolution::Humidity8Temperature12);
+
+        let json = serde_json::to_string(&SupplyVoltage::Low).unwrap();
+        let decoded: SupplyVoltage = serde_json::from_str(&json).unwrap();
+        assert!(matches!(decoded, SupplyVoltage::Low));
+    }
+}
+
+#[cfg(all(test, feature = "std"))]
+mod error_test {
+    use super::Error;
+    use std::error::Error as _;
+    use std::string::ToString;
+
+    #[test]
+    fn std_error() {
+        let error: Error<core::fmt::Error> = Error::I2c(core::fmt::Error);
+        assert_eq!(
+            error.to_string(),
+            "I2C error: an error occurred when formatting an argument"
+        );
+        assert!(error.source().is_some());
+        let error: Error<core::fmt::Error> = Error::Crc {
+            received: [0x4e, 0x85, 0x6c],
+            computed: 0x6b,
+        };
+        assert_eq!(
+            error.to_string(),
+            "invalid CRC: computed 0x6b, received [4e, 85, 6c]"
+        );
+        assert!(error.source().is_none());
+        let error: Error<core::fmt::Error> = Error::CrcMismatch {
+            computed: 0x62,
+            expected: 0x63,
+        };
+        assert_eq!(
+            error.to_string(),
+            "invalid CRC: computed 0x62, expected 0x63"
+        );
+    }
+}
+
+#[cfg(test)]
+mod conversion_test {
+    use super::{raw_from_frame, Humidity, Reading, Resolution, Temperature};
+
+    #[test]
+    fn from_units() {
+        let mut degrees = -46.0;
+        while degrees < 128.0 {
+            let actual = Temperature::from_degrees_celsius(degrees).as_degrees_celsius();
+            // One 14-bit step is about 0.011 °C
+            assert!((actual - degrees).abs() <= 0.011, "{} {}", degrees, actual);
+            degrees += 0.37;
+        }
+        let mut percent = -5.0;
+        while percent < 118.0 {
+            let actual = Humidity::from_percent(percent).as_percent_relative_unclamped();
+            // One 14-bit step is about 0.008%
+            assert!((actual - percent).abs() <= 0.008, "{} {}", percent, actual);
+            percent += 0.29;
+        }
+        assert_eq!(Temperature::from_degrees_celsius(-100.0).as_raw(), 0x0000);
+        assert_eq!(Temperature::from_degrees_celsius(200.0).as_raw(), 0xfffc);
+        assert_eq!(Humidity::from_percent(-10.0).as_raw(), 0x0000);
+        assert_eq!(Humidity::from_percent(150.0).as_raw(), 0xfffc);
+        assert_eq!(Temperature::from_degrees_celsius(7.04).as_raw(), 0x4e84);
+    }
+
+    #[test]
+    fn temperature_units() {
+        let temperature = Temperature(0x4e84);
+        // 7.04 °C
+        let fahrenheit = temperature.as_degrees_fahrenheit();
+        assert!(fahrenheit >= 44.67);
+        assert!(fahrenheit < 44.69);
+        let kelvin = temperature.as_kelvin();
+        assert!(kelvin >= 280.19);
+        assert!(kelvin < 280.20);
+        let celsius = temperature.as_degrees_celsius();
+        assert!((fahrenheit - (celsius * 1.8 + 32.0)).abs() < 1e-4);
+        assert!((kelvin - (celsius + 273.15)).abs() < 1e-4);
+    }
+
+    #[test]
+    fn compensate_heater() {
+        // 7.04 °C with a 1 °C rise from the heater
+        let ambient = Temperature(0x4e84).compensate_heater(1.0);
+        assert!(ambient >= 6.04);
+        assert!(ambient < 6.05);
+    }
+
+    #[test]
+    fn compensate_with() {
+        let humidity = Humidity(0x6838);
+        let uncompensated = humidity.as_percent_relative();
+        // 7.04 °C
+        let temperature = Temperature(0x4e84);
+        let expected = uncompensated + (25.0 - temperature.as_degrees_celsius()) * -0.3;
+        let compensated = humidity.compensate_with(&temperature, -0.3);
+        assert!((compensated - expected).abs() < 1e-4);
+        assert!(compensated >= 39.50);
+        assert!(compensated < 39.51);
+        // No change at the reference temperature
+        let temperature = Temperature::from_degrees_celsius(25.0);
+        let compensated = humidity.compensate_with(&temperature, -0.3);
+        assert!((compensated - uncompensated).abs() < 0.01);
+    }
+
+    #[test]
+    fn delta() {
+        let earlier = Temperature(0x6000);
+        let now = Temperature(0x6400);
+        let expected = now.as_degrees_celsius() - earlier.as_degrees_celsius();
+        assert!((now.delta(&earlier) - expected).abs() < 1e-4);
+        assert!((earlier.delta(&now) + expected).abs() < 1e-4);
+        // 1024 counts of humidity
+        assert_eq!(Humidity(0x6400).delta(&Humidity(0x6000)), 1.953125);
+    }
+
+    #[test]
+    fn corrected() {
+        use crate::CorrectionTable;
+        let table = CorrectionTable::new([(0x0000, 256), (0xfffc, 256)]);
+        let temperature = Temperature(0x6000);
+        let expected = Temperature(0x6100).as_degrees_celsius();
+        assert!((temperature.corrected(&table) - expected).abs() < 1e-4);
+        let humidity = Humidity(0x6000);
+        let expected = Humidity(0x6100).as_percent_relative_unclamped();
+        assert!((humidity.corrected(&table) - expected).abs() < 1e-4);
+    }
+
+    #[test]
+    fn normalized() {
+        let resolution = Resolution::Humidity8Temperature12;
+        assert_eq!(Temperature(0x0000).as_normalized(&resolution), 0.0);
+        assert_eq!(Temperature(0xfffc).as_normalized(&resolution), 1.0);
+        // 12 bits: 0x800 / 0xfff
+        assert_eq!(
+            Temperature(0x8000).as_normalized(&resolution),
+            2048.0 / 4095.0
+        );
+        // 8 bits: 0x80 / 0xff
+        assert_eq!(Humidity(0x8000).as_normalized(&resolution), 128.0 / 255.0);
+    }
+
+    #[test]
+    fn frame_to_raw() {
+        assert_eq!(raw_from_frame(&[0x4e, 0x85, 0x6b]), 0x4e85);
+        assert_eq!(raw_from_frame(&[0x00, 0xff, 0x00]), 0x00ff);
+    }
+
+    #[test]
+    fn status_bits_not_error() {
+        // Status bit set, masked value 0x0000
+        match Reading::<Humidity>::from_raw(0x0002) {
+            Reading::Ok(humidity) => assert_eq!(humidity.as_raw(), 0x0000),
+            _ => panic!("Expected Ok"),
+        }
+        assert!(Reading::<Humidity>::from_raw(0x0000).is_error_low());
+        assert!(Reading::<Humidity>::from_raw(0xffff).is_error_high());
+    }
+
+    #[test]
+    fn temperature_millidegrees() {
+        for raw in (0..=0xfffc).step_by(4) {
+            let expected = -46850.0 + 175720.0 / 65536.0 * f64::from(raw);
+            let actual = f64::from(Temperature(raw).as_millidegrees_celsius());
+            assert!(
+                (actual - expected).abs() <= 0.5,
+                "raw {:#06x}: expected {}, got {}",
+                raw,
+                expected,
+                actual
+            );
+        }
+        assert_eq!(Temperature(0x4e84).as_millidegrees_celsius(), 7044);
+    }
+
+    #[test]
+    fn humidity_permille_unclamped() {
+        for raw in (0..=0xfffc).step_by(4) {
+            let humidity = Humidity(raw);
+            let expected = humidity.as_percent_relative_unclamped() * 10.0;
+            let actual = humidity.as_permille_unclamped() as f32;
+            assert!(
+                (actual - expected).abs() <= 0.5,
+                "raw {:#06x}: expected {}, got {}",
+                raw,
+                expected,
+                actual
+            );
+        }
+        assert_eq!(Humidity(0x0000).as_permille_unclamped(), -60);
+        assert_eq!(Humidity(0xf000).as_permille_unclamped(), 1112);
+    }
+
+    #[test]
+    fn humidity_permille() {
+        for raw in (0..=0xfffc).step_by(4) {
+            let humidity = Humidity(raw);
+            let expected = humidity.as_percent_relative() * 10.0;
+            let actual = f32::from(humidity.as_permille());
+            assert!(
+                (actual - expected).abs() <= 0.5,
+                "raw {:#06x}: expected {}, got {}",
+                raw,
+                expected,
+                actual
+            );
+        }
+    }
 }
@@ -0,0 +1,42 @@
+//! Detection of NAK errors from I2C drivers
+
+/// An I2C error that can indicate a NAK (negative acknowledge)
+///
+/// While the sensor is measuring without clock stretching, it does not acknowledge its
+/// address. Implementing this trait for an I2C driver's error type allows
+/// `ResultReader::read_result_auto` to detect this without a closure.
+///
+/// With the `hal-1` feature, this trait is implemented for all embedded-hal 1.0 I2C error
+/// types.
+pub trait NakError {
+    /// Returns true if this error is a NAK
+    fn is_nak(&self) -> bool;
+}
+
+#[cfg(feature = "hal-1")]
+impl<T> NakError for T
+where
+    T: embedded_hal_1::i2c::Error,
+{
+    fn is_nak(&self) -> bool {
+        matches!(
+            self.kind(),
+            embedded_hal_1::i2c::ErrorKind::NoAcknowledge(_)
+        )
+    }
+}
+
+#[cfg(test)]
+mod nak_test {
+    #[test]
+    #[cfg(feature = "hal-1")]
+    fn hal_1_errors() {
+        use super::NakError;
+        use embedded_hal_1::i2c::{ErrorKind, NoAcknowledgeSource};
+
+        assert!(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Address).is_nak());
+        assert!(ErrorKind::NoAcknowledge(NoAcknowledgeSource::Unknown).is_nak());
+        assert!(!ErrorKind::Bus.is_nak());
+        assert!(!ErrorKind::ArbitrationLoss.is_nak());
+    }
+}
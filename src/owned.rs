@@ -0,0 +1,106 @@
+//! A driver that owns the I2C bus
+
+use embedded_hal::blocking::delay::DelayMs;
+use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
+
+use crate::{Error, Htu2xd, Humidity, Reading, Temperature, UserRegister, ADDRESS};
+
+/// HTU2XD driver that owns the I2C bus
+///
+/// This is convenient when the sensor is the only device on the bus. The methods work like
+/// the `Htu2xd` methods with the same names, but do not take an I2C argument. Use `release`
+/// to get the bus back.
+///
+/// For other `Htu2xd` functions, `parts` provides access to the driver and the bus.
+pub struct Htu2xdOwned<I> {
+    driver: Htu2xd<I>,
+    i2c: I,
+}
+
+impl<I, E> Htu2xdOwned<I>
+where
+    I: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Creates a driver object that owns an I2C bus, but does not perform any initialization
+    ///
+    /// The driver uses the sensor's default address, 0x40.
+    pub fn new(i2c: I) -> Self {
+        Htu2xdOwned::new_with_address(i2c, ADDRESS)
+    }
+
+    /// Creates a driver object that owns an I2C bus and communicates with a sensor at a
+    /// non-default I2C address, but does not perform any initialization
+    pub fn new_with_address(i2c: I, address: u8) -> Self {
+        Htu2xdOwned {
+            driver: Htu2xd::new_with_address(address),
+            i2c,
+        }
+    }
+
+    /// Returns the I2C address that this driver uses
+    pub fn address(&self) -> u8 {
+        self.driver.address()
+    }
+
+    /// Destroys this driver and returns the I2C bus
+    pub fn release(self) -> I {
+        self.i2c
+    }
+
+    /// Returns the underlying driver and the I2C bus
+    pub fn parts(&mut self) -> (&mut Htu2xd<I>, &mut I) {
+        (&mut self.driver, &mut self.i2c)
+    }
+
+    /// Resets the sensor and restores default settings, but does not restore the heater enable bit
+    ///
+    /// After this function returns the sensor may take up to 15 ms to reset.
+    pub fn soft_reset(&mut self) -> Result<(), E> {
+        self.driver.soft_reset(&mut self.i2c)
+    }
+
+    /// Checks that a sensor is responding with a plausible user register value
+    pub fn probe(&mut self) -> Result<bool, E> {
+        self.driver.probe(&mut self.i2c)
+    }
+
+    /// Reads the current humidity, with clock stretching
+    pub fn read_humidity_blocking(&mut self) -> Result<Reading<Humidity>, Error<E>> {
+        self.driver.read_humidity_blocking(&mut self.i2c)
+    }
+
+    /// Reads the current temperature, with clock stretching
+    pub fn read_temperature_blocking(&mut self) -> Result<Reading<Temperature>, Error<E>> {
+        self.driver.read_temperature_blocking(&mut self.i2c)
+    }
+
+    /// Reads the current temperature and then the current humidity, with clock stretching
+    pub fn read_measurements_blocking(
+        &mut self,
+    ) -> Result<(Reading<Temperature>, Reading<Humidity>), Error<E>> {
+        self.driver.read_measurements_blocking(&mut self.i2c)
+    }
+
+    /// Measures the temperature and then the humidity, without clock stretching
+    pub fn read_climate<D>(&mut self, delay: &mut D) -> Result<(Temperature, Humidity), Error<E>>
+    where
+        D: DelayMs<u32>,
+    {
+        self.driver.read_climate(&mut self.i2c, delay)
+    }
+
+    /// Reads the user register and returns its content
+    pub fn read_user_register(&mut self) -> Result<UserRegister, E> {
+        self.driver.read_user_register(&mut self.i2c)
+    }
+
+    /// Writes the user register
+    pub fn write_user_register(&mut self, register: UserRegister) -> Result<(), E> {
+        self.driver.write_user_register(&mut self.i2c, register)
+    }
+
+    /// Reads the 64-bit electronic serial number of the sensor
+    pub fn read_serial_number(&mut self) -> Result<[u8; 8], Error<E>> {
+        self.driver.read_serial_number(&mut self.i2c)
+    }
+}
@@ -0,0 +1,58 @@
+//! Compact storage of readings
+
+use crate::{Humidity, Temperature};
+
+/// Packs a temperature and humidity into 4 bytes
+///
+/// The result is a big-endian 32-bit value with this layout:
+///
+/// | Bits | Content |
+/// |------|---------|
+/// | 31-26 | Zero |
+/// | 25-12 | The 14 most significant bits of the raw temperature |
+/// | 11-0 | The 12 most significant bits of the raw humidity |
+///
+/// The other raw bits are discarded, and `unpack_climate` returns them as zero. This includes
+/// the two status bits of each reading and bits 3 and 2 of the raw humidity, so the round trip
+/// is not exact: a raw humidity of 0x6838 is unpacked as 0x6830. The sensor measures humidity
+/// with at most 12 bits, so bits 3 and 2 are below the precision of any humidity
+/// measurement.
+pub fn pack_climate(temperature: &Temperature, humidity: &Humidity) -> [u8; 4] {
+    let temperature_bits = u32::from(temperature.as_raw() >> 2);
+    let humidity_bits = u32::from(humidity.as_raw() >> 4);
+    ((temperature_bits << 12) | humidity_bits).to_be_bytes()
+}
+
+/// Unpacks a temperature and humidity that were packed with `pack_climate`
+///
+/// The upper 6 bits are ignored.
+pub fn unpack_climate(packed: [u8; 4]) -> (Temperature, Humidity) {
+    let value = u32::from_be_bytes(packed);
+    let temperature_bits = ((value >> 12) & 0x3fff) as u16;
+    let humidity_bits = (value & 0xfff) as u16;
+    (
+        Temperature(temperature_bits << 2),
+        Humidity(humidity_bits << 4),
+    )
+}
+
+#[cfg(test)]
+mod packing_test {
+    use super::{pack_climate, unpack_climate};
+    use crate::{Humidity, Temperature};
+
+    #[test]
+    fn round_trip() {
+        let packed = pack_climate(&Temperature(0x4e84), &Humidity(0x6838));
+        assert_eq!(packed, [0x01, 0x3a, 0x16, 0x83]);
+        let (temperature, humidity) = unpack_climate(packed);
+        assert_eq!(temperature.as_raw(), 0x4e84);
+        // Humidity bits 3 and 2 are discarded
+        assert_eq!(humidity.as_raw(), 0x6830);
+
+        let (temperature, humidity) =
+            unpack_climate(pack_climate(&Temperature(0xfffc), &Humidity(0xfff0)));
+        assert_eq!(temperature.as_raw(), 0xfffc);
+        assert_eq!(humidity.as_raw(), 0xfff0);
+    }
+}
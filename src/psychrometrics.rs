@@ -0,0 +1,395 @@
+//! Calculations derived from temperature and humidity readings
+
+use crate::{Humidity, Temperature};
+
+/// Magnus coefficient a (dimensionless)
+#[cfg(feature = "libm")]
+const MAGNUS_A: f32 = 17.27;
+/// Magnus coefficient b (degrees Celsius)
+#[cfg(feature = "libm")]
+const MAGNUS_B: f32 = 237.7;
+/// Magnus coefficient a over ice (dimensionless)
+#[cfg(feature = "libm")]
+const MAGNUS_ICE_A: f32 = 21.875;
+/// Magnus coefficient b over ice (degrees Celsius)
+#[cfg(feature = "libm")]
+const MAGNUS_ICE_B: f32 = 265.5;
+/// Saturation vapor pressure at 0 °C, hectopascals
+#[cfg(feature = "libm")]
+const SATURATION_PRESSURE_0C_HPA: f32 = 6.112;
+/// Ratio of the molecular masses of water and dry air, multiplied by 1000 (grams per kilogram)
+#[cfg(feature = "libm")]
+const MOLECULAR_MASS_RATIO_G_PER_KG: f32 = 621.97;
+
+/// Standard sea-level pressure, hectopascals
+#[cfg(feature = "libm")]
+const STANDARD_PRESSURE_HPA: f32 = 1013.25;
+/// Standard sea-level temperature, kelvins
+#[cfg(feature = "libm")]
+const STANDARD_TEMPERATURE_K: f32 = 288.15;
+/// Offset from degrees Celsius to kelvins
+#[cfg(feature = "libm")]
+const CELSIUS_TO_KELVIN: f32 = 273.15;
+/// 1 / (specific gas constant of water vapor), multiplied by 100 000 to convert hectopascals
+/// and kilograms to pascals and grams (g K / (hPa m³))
+#[cfg(feature = "libm")]
+const VAPOR_DENSITY_FACTOR: f32 = 216.7;
+
+/// Magnus coefficient a (dimensionless), multiplied by 100
+const MAGNUS_A_CENTI: i64 = 1727;
+/// Magnus coefficient b (degrees Celsius), multiplied by 100
+const MAGNUS_B_CENTI: i64 = 23770;
+
+/// ln(n / 100) for n = 1..=100, in fixed point with 12 fractional bits
+const LN_PERCENT_Q12: [i16; 100] = [
+    -18863, -16024, -14363, -13185, -12271, -11524, -10892, -10345, -9863, -9431, -9041, -8685,
+    -8357, -8053, -7771, -7506, -7258, -7024, -6802, -6592, -6392, -6202, -6020, -5845, -5678,
+    -5518, -5363, -5214, -5070, -4931, -4797, -4667, -4541, -4419, -4300, -4185, -4072, -3963,
+    -3857, -3753, -3652, -3553, -3457, -3363, -3271, -3181, -3093, -3006, -2922, -2839, -2758,
+    -2678, -2600, -2524, -2449, -2375, -2302, -2231, -2161, -2092, -2025, -1958, -1892, -1828,
+    -1764, -1702, -1640, -1580, -1520, -1461, -1403, -1346, -1289, -1233, -1178, -1124, -1071,
+    -1018, -966, -914, -863, -813, -763, -714, -666, -618, -570, -524, -477, -432, -386, -342,
+    -297, -253, -210, -167, -125, -83, -41, 0,
+];
+
+/// Calculates the dew point in tenths of a degree Celsius using only integer operations
+///
+/// This function uses the Magnus formula (a = 17.27, b = 237.7 °C) with a lookup table
+/// for the logarithm, so it does not need floating-point support or `libm`.
+///
+/// For temperatures from 0 to 40 °C and relative humidities from 20% to 90%, the result is
+/// within 0.5 °C of the same formula evaluated with floating-point operations. Relative
+/// humidity values below 1% are treated as 1%, and values above 100% are treated as 100%.
+pub fn dew_point_decidegrees(temperature: &Temperature, humidity: &Humidity) -> i16 {
+    // Temperature in hundredths of a degree Celsius
+    let centidegrees = -4685 + ((17572 * i64::from(temperature.0)) >> 16);
+    // Humidity in tenths of a percent
+    let permille = (-60 + ((1250 * i64::from(humidity.0)) >> 16)).clamp(10, 1000);
+
+    // ln(humidity), interpolated linearly between whole percentages
+    let percent = (permille / 10) as usize;
+    let ln_humidity = if percent == 100 {
+        i64::from(LN_PERCENT_Q12[99])
+    } else {
+        let below = i64::from(LN_PERCENT_Q12[percent - 1]);
+        let above = i64::from(LN_PERCENT_Q12[percent]);
+        below + (above - below) * (permille % 10) / 10
+    };
+    // gamma = ln(humidity) + a * T / (b + T)
+    let gamma = ln_humidity
+        + (MAGNUS_A_CENTI * centidegrees * 4096) / (100 * (MAGNUS_B_CENTI + centidegrees));
+    // dew point = b * gamma / (a - gamma)
+    let numerator = 10 * MAGNUS_B_CENTI * gamma;
+    let denominator = MAGNUS_A_CENTI * 4096 - 100 * gamma;
+    divide_rounded(numerator, denominator) as i16
+}
+
+/// Calculates the dew point in degrees Celsius
+///
+/// This function uses the Magnus formula (a = 17.27, b = 237.7 °C). Relative humidity values
+/// below 0.1% are treated as 0.1%.
+///
+/// This function requires the `libm` feature.
+#[cfg(feature = "libm")]
+pub fn dew_point(temperature: &Temperature, humidity: &Humidity) -> f32 {
+    let degrees = temperature.as_degrees_celsius();
+    let fraction = humidity.as_percent_relative().max(0.1) / 100.0;
+    let gamma = magnus_gamma(degrees, fraction);
+    MAGNUS_B * gamma / (MAGNUS_A - gamma)
+}
+
+/// Calculates the frost point in degrees Celsius
+///
+/// The frost point is the temperature at which the air becomes saturated with respect to
+/// ice. This function calculates the vapor pressure from the relative humidity (which the
+/// sensor reports with respect to water) using the Magnus formula over water
+/// (a = 17.27, b = 237.7 °C), and then finds the frost point using the Magnus formula over
+/// ice (a = 21.875, b = 265.5 °C). Relative humidity values below 0.1% are treated as 0.1%.
+///
+/// The result is only meaningful when the frost point is below 0 °C. Above freezing, use
+/// `dew_point` instead.
+///
+/// This function requires the `libm` feature.
+#[cfg(feature = "libm")]
+pub fn frost_point(temperature: &Temperature, humidity: &Humidity) -> f32 {
+    let degrees = temperature.as_degrees_celsius();
+    let fraction = humidity.as_percent_relative().max(0.1) / 100.0;
+    let gamma = magnus_gamma(degrees, fraction);
+    MAGNUS_ICE_B * gamma / (MAGNUS_ICE_A - gamma)
+}
+
+/// Calculates the dew point in degrees Celsius, with safeguards for humidity near 0% and
+/// 100%
+///
+/// This function uses the Magnus formula (a = 17.27, b = 237.7 °C) with these safeguards:
+///
+/// * The relative humidity is clamped to the range 0.1% to 100% before taking its logarithm,
+///   so the logarithm is always finite and never positive. Humidity readings above 100%,
+///   which the sensor can report near saturation, are treated as 100%.
+/// * The result is limited to the measured temperature, because the dew point cannot be
+///   above the air temperature.
+///
+/// Within the sensor's measurement range, the result is never NaN.
+///
+/// This function requires the `libm` feature.
+#[cfg(feature = "libm")]
+pub fn dew_point_celsius_stable(temperature: &Temperature, humidity: &Humidity) -> f32 {
+    dew_point(temperature, humidity).min(temperature.as_degrees_celsius())
+}
+
+/// Calculates the mixing ratio (mass of water vapor per mass of dry air) in grams per
+/// kilogram
+///
+/// The mixing ratio is `621.97 * e / (p - e)`, where `e` is the actual vapor pressure and `p`
+/// is the total air pressure in hectopascals. The vapor pressure is calculated from the
+/// temperature and humidity using the Magnus formula (a = 17.27, b = 237.7 °C).
+///
+/// This function requires the `libm` feature.
+#[cfg(feature = "libm")]
+pub fn mixing_ratio_g_per_kg(
+    temperature: &Temperature,
+    humidity: &Humidity,
+    pressure_hpa: f32,
+) -> f32 {
+    let vapor_pressure = vapor_pressure_hpa(temperature, humidity);
+    MOLECULAR_MASS_RATIO_G_PER_KG * vapor_pressure / (pressure_hpa - vapor_pressure)
+}
+
+/// Calculates the absolute humidity (mass of water vapor per volume of air) in grams per
+/// cubic meter
+///
+/// The absolute humidity is `216.7 * e / T`, where `e` is the actual vapor pressure in
+/// hectopascals and `T` is the temperature in kelvins. The vapor pressure is the saturation
+/// vapor pressure from the Magnus formula (a = 17.27, b = 237.7 °C), multiplied by the
+/// relative humidity.
+///
+/// The Magnus formula is accurate from about -45 to 60 °C, so temperatures outside that range
+/// are clamped to it. The relative humidity is clamped to 0% to 100%.
+///
+/// This function requires the `libm` feature.
+#[cfg(feature = "libm")]
+pub fn absolute_humidity(temperature: &Temperature, humidity: &Humidity) -> f32 {
+    let degrees = temperature.as_degrees_celsius().clamp(-45.0, 60.0);
+    let vapor_pressure =
+        saturation_vapor_pressure_hpa(degrees) * humidity.as_percent_relative() / 100.0;
+    VAPOR_DENSITY_FACTOR * vapor_pressure / (degrees + CELSIUS_TO_KELVIN)
+}
+
+/// Calculates the density altitude in meters
+///
+/// The density altitude is the altitude in the International Standard Atmosphere that has
+/// the same air density as the current conditions. `pressure_hpa` is the actual (station)
+/// air pressure in hectopascals, not the pressure adjusted to sea level.
+///
+/// This function calculates the virtual temperature from the vapor pressure (using the
+/// Magnus formula), calculates the air density relative to standard sea-level conditions,
+/// and finds the altitude with that density in the troposphere of the standard atmosphere
+/// (temperature lapse rate 6.5 K/km). The result is only meaningful below about 11 km.
+///
+/// This function requires the `libm` feature.
+#[cfg(feature = "libm")]
+pub fn density_altitude_m(
+    temperature: &Temperature,
+    humidity: &Humidity,
+    pressure_hpa: f32,
+) -> f32 {
+    /// Altitude scale T0 / L, meters
+    const ALTITUDE_SCALE_M: f32 = 44330.8;
+    /// Exponent 1 / (g / (R * L) - 1)
+    const DENSITY_EXPONENT: f32 = 0.234969;
+
+    let vapor_pressure = vapor_pressure_hpa(temperature, humidity);
+    let kelvins = temperature.as_degrees_celsius() + CELSIUS_TO_KELVIN;
+    let virtual_temperature = kelvins / (1.0 - 0.378 * vapor_pressure / pressure_hpa);
+    let density_ratio =
+        (pressure_hpa / STANDARD_PRESSURE_HPA) * (STANDARD_TEMPERATURE_K / virtual_temperature);
+    ALTITUDE_SCALE_M * (1.0 - libm::powf(density_ratio, DENSITY_EXPONENT))
+}
+
+/// Calculates the Magnus gamma value `ln(RH) + a * T / (b + T)` over water
+///
+/// `fraction` is the relative humidity as a fraction (1.0 = 100%).
+#[cfg(feature = "libm")]
+fn magnus_gamma(degrees: f32, fraction: f32) -> f32 {
+    libm::logf(fraction) + MAGNUS_A * degrees / (MAGNUS_B + degrees)
+}
+
+/// Calculates the saturation vapor pressure over water in hectopascals
+#[cfg(feature = "libm")]
+fn saturation_vapor_pressure_hpa(degrees_celsius: f32) -> f32 {
+    SATURATION_PRESSURE_0C_HPA
+        * libm::expf(MAGNUS_A * degrees_celsius / (MAGNUS_B + degrees_celsius))
+}
+
+/// Calculates the actual vapor pressure in hectopascals
+#[cfg(feature = "libm")]
+fn vapor_pressure_hpa(temperature: &Temperature, humidity: &Humidity) -> f32 {
+    saturation_vapor_pressure_hpa(temperature.as_degrees_celsius()) * humidity.as_percent_relative()
+        / 100.0
+}
+
+/// A classification of temperature and humidity for human comfort
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum ComfortZone {
+    /// Temperature from 20 to 26 °C and relative humidity from 30% to 60%
+    Comfortable,
+    /// Temperature below 20 °C
+    Cold,
+    /// Temperature above 26 °C
+    Hot,
+    /// Temperature comfortable, relative humidity below 30%
+    Dry,
+    /// Temperature comfortable, relative humidity above 60%
+    Humid,
+}
+
+/// Classifies a temperature and humidity for human comfort
+///
+/// This uses a simple indoor comfort range of 20 to 26 °C and 30% to 60% relative humidity.
+/// If the temperature is outside its range, the result is `Cold` or `Hot` regardless of the
+/// humidity.
+pub fn comfort_zone(temperature: &Temperature, humidity: &Humidity) -> ComfortZone {
+    let degrees = temperature.as_degrees_celsius();
+    let percent = humidity.as_percent_relative();
+    if degrees < 20.0 {
+        ComfortZone::Cold
+    } else if degrees > 26.0 {
+        ComfortZone::Hot
+    } else if percent < 30.0 {
+        ComfortZone::Dry
+    } else if percent > 60.0 {
+        ComfortZone::Humid
+    } else {
+        ComfortZone::Comfortable
+    }
+}
+
+/// Divides and rounds to the nearest integer (denominator must be positive)
+fn divide_rounded(numerator: i64, denominator: i64) -> i64 {
+    if numerator >= 0 {
+        (numerator + denominator / 2) / denominator
+    } else {
+        (numerator - denominator / 2) / denominator
+    }
+}
+
+#[cfg(test)]
+mod psychrometrics_test {
+    extern crate std;
+
+    use super::dew_point_decidegrees;
+    use crate::{Humidity, Temperature};
+
+    /// Dew point from the Magnus formula with floating-point operations
+    fn dew_point_float(temperature: &Temperature, humidity: &Humidity) -> f32 {
+        let t = temperature.as_degrees_celsius();
+        let gamma = (humidity.as_percent_relative() / 100.0).ln() + 17.27 * t / (237.7 + t);
+        237.7 * gamma / (17.27 - gamma)
+    }
+
+    #[test]
+    #[cfg(feature = "libm")]
+    fn stable_dew_point() {
+        use super::dew_point_celsius_stable;
+        // Full raw range of temperature, and humidity from below 0% to above 100%
+        for temperature_raw in (0..=0xfffc).step_by(0x400) {
+            for humidity_raw in (0..=0xfffc).step_by(0x400) {
+                let temperature = Temperature(temperature_raw);
+                let dew_point = dew_point_celsius_stable(&temperature, &Humidity(humidity_raw));
+                assert!(!dew_point.is_nan());
+                assert!(dew_point <= temperature.as_degrees_celsius());
+            }
+        }
+        // 111% relative humidity is saturated
+        let temperature = Temperature(0x6000);
+        assert_eq!(
+            dew_point_celsius_stable(&temperature, &Humidity(0xf000)),
+            temperature.as_degrees_celsius()
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "libm")]
+    fn float_dew_point() {
+        use super::dew_point;
+        // 20 °C, 50% relative humidity
+        let value = dew_point(&Temperature(24932), &Humidity(29360));
+        assert!((value - 9.26).abs() < 0.1, "{}", value);
+        // 25 °C, 60% relative humidity
+        let value = dew_point(&Temperature(26796), &Humidity(34600));
+        assert!((value - 16.69).abs() < 0.1, "{}", value);
+        // 0% relative humidity
+        assert!(dew_point(&Temperature(24932), &Humidity(0)).is_finite());
+    }
+
+    #[test]
+    #[cfg(feature = "libm")]
+    fn sub_zero_frost_point() {
+        use super::{dew_point, frost_point};
+        // -10 °C, 80% relative humidity
+        let temperature = Temperature::from_degrees_celsius(-10.0);
+        let humidity = Humidity::from_percent(80.0);
+        let value = frost_point(&temperature, &humidity);
+        assert!((value - -11.40).abs() < 0.05, "{}", value);
+        // The frost point is above the dew point
+        let dew = dew_point(&temperature, &humidity);
+        assert!((dew - -12.78).abs() < 0.05, "{}", dew);
+    }
+
+    #[test]
+    #[cfg(feature = "libm")]
+    fn absolute() {
+        use super::absolute_humidity;
+        // 25 °C, 60% relative humidity
+        let value = absolute_humidity(&Temperature(26796), &Humidity(34600));
+        assert!((value - 13.8).abs() < 0.1, "{}", value);
+        // 0% relative humidity
+        assert_eq!(absolute_humidity(&Temperature(26796), &Humidity(0)), 0.0);
+    }
+
+    #[test]
+    #[cfg(feature = "libm")]
+    fn mixing_ratio() {
+        use super::mixing_ratio_g_per_kg;
+        // 25 °C, 50% relative humidity, standard sea-level pressure
+        let ratio = mixing_ratio_g_per_kg(&Temperature(26797), &Humidity(29360), 1013.25);
+        assert!(ratio >= 9.8);
+        assert!(ratio < 9.9);
+    }
+
+    #[test]
+    #[cfg(feature = "libm")]
+    fn density_altitude() {
+        use super::density_altitude_m;
+        // Standard conditions: 15 °C, 0% relative humidity
+        let altitude = density_altitude_m(&Temperature(23067), &Humidity(3146), 1013.25);
+        assert!(altitude.abs() < 1.0);
+        // 30 °C, 50% relative humidity
+        let altitude = density_altitude_m(&Temperature(28662), &Humidity(29360), 900.0);
+        assert!(altitude >= 1815.0);
+        assert!(altitude < 1820.0);
+    }
+
+    #[test]
+    fn integer_dew_point_matches_float() {
+        // Raw values covering about 0 to 40 °C
+        for temperature_raw in (17476..=32380).step_by(148) {
+            // Raw values covering about 20% to 90% relative humidity
+            for humidity_raw in (13631..=50332).step_by(524) {
+                let temperature = Temperature(temperature_raw);
+                let humidity = Humidity(humidity_raw);
+                let expected = dew_point_float(&temperature, &humidity);
+                let actual = f32::from(dew_point_decidegrees(&temperature, &humidity)) / 10.0;
+                assert!(
+                    (actual - expected).abs() <= 0.5,
+                    "T {} °C, RH {}%: expected {}, got {}",
+                    temperature.as_degrees_celsius(),
+                    humidity.as_percent_relative(),
+                    expected,
+                    actual
+                );
+            }
+        }
+    }
+}
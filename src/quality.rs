@@ -0,0 +1,149 @@
+//! Estimates of measurement quality
+
+use crate::{Humidity, Resolution, SupplyVoltage, Temperature, HUMIDITY_TEMPERATURE_COEFFICIENT};
+
+/// Typical temperature accuracy from the datasheet, degrees Celsius
+const TEMPERATURE_ACCURACY_C: f32 = 0.3;
+/// Typical relative humidity accuracy from the datasheet, percent
+const HUMIDITY_ACCURACY_PCT: f32 = 2.0;
+/// Temperature where humidity readings need no temperature compensation, degrees Celsius
+const COMPENSATION_REFERENCE_C: f32 = 25.0;
+
+/// A rough level of confidence in a measurement
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum Confidence {
+    /// The measurement may be much less accurate than the datasheet specifies
+    Low,
+    /// The measurement is somewhat coarse, but within the datasheet accuracy
+    Medium,
+    /// The measurement has the best resolution and accuracy that the sensor supports
+    High,
+}
+
+/// Estimates the confidence in a measurement taken with a resolution and supply voltage
+///
+/// The heuristic is:
+///
+/// * If the supply voltage was low, the datasheet accuracy does not apply, so the confidence
+///   is `Low` at any resolution
+/// * Otherwise, the highest resolution (12-bit humidity, 14-bit temperature) gives `High`
+///   confidence, the 10-bit and 11-bit humidity resolutions give `Medium` confidence,
+///   and the 8-bit humidity resolution gives `Low` confidence
+pub fn reading_confidence(resolution: &Resolution, supply: &SupplyVoltage) -> Confidence {
+    match supply {
+        SupplyVoltage::Low => Confidence::Low,
+        SupplyVoltage::High => match resolution.humidity_bits() {
+            12 => Confidence::High,
+            10 | 11 => Confidence::Medium,
+            _ => Confidence::Low,
+        },
+    }
+}
+
+/// A temperature and humidity measurement with estimated uncertainties
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct ClimateSample {
+    /// The temperature
+    pub temperature: Temperature,
+    /// The humidity
+    pub humidity: Humidity,
+    /// Uncertainty of `temperature.as_degrees_celsius()`, plus or minus degrees Celsius
+    pub temperature_uncertainty_c: f32,
+    /// Uncertainty of `humidity.as_percent_relative()`, plus or minus percent relative
+    /// humidity
+    ///
+    /// This includes the error from not compensating the humidity for the temperature.
+    pub humidity_uncertainty_pct: f32,
+    /// Uncertainty of `humidity.compensate(&temperature)`, plus or minus percent relative
+    /// humidity
+    pub compensated_humidity_uncertainty_pct: f32,
+}
+
+impl ClimateSample {
+    /// Creates a sample and estimates its uncertainties from the resolution and supply
+    /// voltage that were used for the measurements
+    ///
+    /// Each uncertainty is the typical datasheet accuracy (0.3 °C or 2% relative humidity)
+    /// plus half of the resolution step. If the supply voltage was low, the datasheet accuracy
+    /// does not apply, and the uncertainties are doubled. The uncompensated humidity
+    /// uncertainty also includes the humidity temperature coefficient (0.15% per °C)
+    /// multiplied by the difference between the temperature and 25 °C.
+    pub fn new(
+        temperature: Temperature,
+        humidity: Humidity,
+        resolution: &Resolution,
+        supply: &SupplyVoltage,
+    ) -> Self {
+        let supply_factor = match supply {
+            SupplyVoltage::High => 1.0,
+            SupplyVoltage::Low => 2.0,
+        };
+        let temperature_step = 175.72 / (1u32 << resolution.temperature_bits()) as f32;
+        let humidity_step = 125.0 / (1u32 << resolution.humidity_bits()) as f32;
+        let temperature_uncertainty_c =
+            supply_factor * (TEMPERATURE_ACCURACY_C + temperature_step / 2.0);
+        let compensated_humidity_uncertainty_pct =
+            supply_factor * (HUMIDITY_ACCURACY_PCT + humidity_step / 2.0);
+        let compensation_error = (HUMIDITY_TEMPERATURE_COEFFICIENT
+            * (temperature.as_degrees_celsius() - COMPENSATION_REFERENCE_C))
+            .abs();
+        ClimateSample {
+            temperature,
+            humidity,
+            temperature_uncertainty_c,
+            humidity_uncertainty_pct: compensated_humidity_uncertainty_pct + compensation_error,
+            compensated_humidity_uncertainty_pct,
+        }
+    }
+}
+
+#[cfg(test)]
+mod quality_test {
+    use super::{reading_confidence, ClimateSample, Confidence};
+    use crate::{Humidity, Resolution, SupplyVoltage, Temperature};
+
+    #[test]
+    fn uncertainty() {
+        // 25 °C
+        let temperature = Temperature(26797);
+        let sample = ClimateSample::new(
+            temperature.clone(),
+            Humidity(0x6838),
+            &Resolution::Humidity8Temperature12,
+            &SupplyVoltage::High,
+        );
+        assert!((sample.temperature_uncertainty_c - 0.3214).abs() < 0.001);
+        assert!((sample.compensated_humidity_uncertainty_pct - 2.2441).abs() < 0.001);
+        assert!((sample.humidity_uncertainty_pct - 2.2441).abs() < 0.001);
+
+        let sample = ClimateSample::new(
+            temperature,
+            Humidity(0x6838),
+            &Resolution::Humidity8Temperature12,
+            &SupplyVoltage::Low,
+        );
+        assert!((sample.temperature_uncertainty_c - 0.6429).abs() < 0.001);
+    }
+
+    #[test]
+    fn confidence() {
+        let expected = [
+            (Resolution::Humidity12Temperature14, Confidence::High),
+            (Resolution::Humidity8Temperature12, Confidence::Low),
+            (Resolution::Humidity10Temperature13, Confidence::Medium),
+            (Resolution::Humidity11Temperature11, Confidence::Medium),
+        ];
+        for (resolution, confidence) in expected.iter() {
+            assert_eq!(
+                reading_confidence(resolution, &SupplyVoltage::High),
+                *confidence
+            );
+            assert_eq!(
+                reading_confidence(resolution, &SupplyVoltage::Low),
+                Confidence::Low
+            );
+        }
+    }
+}
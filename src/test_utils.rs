@@ -0,0 +1,22 @@
+//! Helpers for writing tests that simulate a sensor
+//!
+//! This module is only available with the `test-utils` feature.
+
+use crate::{Crc, MeasurementType};
+
+/// Builds a 3-byte measurement frame as the sensor would send it
+///
+/// The two least significant bits of `raw` are replaced with the status bits for the
+/// measurement type, and a correct CRC byte is appended.
+pub fn build_reading_frame(raw: u16, kind: MeasurementType) -> [u8; 3] {
+    let status = match kind {
+        MeasurementType::Temperature => 0b00,
+        MeasurementType::Humidity => 0b10,
+    };
+    let value = (raw & 0xfffc) | status;
+    let [msb, lsb] = value.to_be_bytes();
+
+    let mut crc = Crc::new();
+    crc.add_all(&[msb, lsb]);
+    [msb, lsb, crc.value()]
+}
@@ -0,0 +1,139 @@
+//! Measurement timing
+
+use crate::{MeasurementType, Resolution};
+
+/// Maximum conversion time for a 14-bit temperature measurement, milliseconds
+pub const TEMPERATURE_14_BIT_TIME_MS: u32 = 50;
+/// Maximum conversion time for a 13-bit temperature measurement, milliseconds
+pub const TEMPERATURE_13_BIT_TIME_MS: u32 = 25;
+/// Maximum conversion time for a 12-bit temperature measurement, milliseconds
+pub const TEMPERATURE_12_BIT_TIME_MS: u32 = 13;
+/// Maximum conversion time for an 11-bit temperature measurement, milliseconds
+pub const TEMPERATURE_11_BIT_TIME_MS: u32 = 7;
+/// Maximum conversion time for a 12-bit humidity measurement, milliseconds
+pub const HUMIDITY_12_BIT_TIME_MS: u32 = 16;
+/// Maximum conversion time for an 11-bit humidity measurement, milliseconds
+pub const HUMIDITY_11_BIT_TIME_MS: u32 = 8;
+/// Maximum conversion time for a 10-bit humidity measurement, milliseconds
+pub const HUMIDITY_10_BIT_TIME_MS: u32 = 5;
+/// Maximum conversion time for an 8-bit humidity measurement, milliseconds
+pub const HUMIDITY_8_BIT_TIME_MS: u32 = 3;
+
+/// Returns the maximum time that the sensor takes to measure a quantity at a resolution,
+/// in milliseconds
+///
+/// These values are the maximum conversion times from the datasheet:
+///
+/// | Resolution | Humidity | Temperature |
+/// |------------|----------|-------------|
+/// | RH 12 bits, T 14 bits | 16 ms | 50 ms |
+/// | RH 8 bits, T 12 bits | 3 ms | 13 ms |
+/// | RH 10 bits, T 13 bits | 5 ms | 25 ms |
+/// | RH 11 bits, T 11 bits | 8 ms | 7 ms |
+pub fn conversion_time_ms(resolution: &Resolution, kind: MeasurementType) -> u32 {
+    match kind {
+        MeasurementType::Temperature => match resolution {
+            Resolution::Humidity12Temperature14 => TEMPERATURE_14_BIT_TIME_MS,
+            Resolution::Humidity8Temperature12 => TEMPERATURE_12_BIT_TIME_MS,
+            Resolution::Humidity10Temperature13 => TEMPERATURE_13_BIT_TIME_MS,
+            Resolution::Humidity11Temperature11 => TEMPERATURE_11_BIT_TIME_MS,
+        },
+        MeasurementType::Humidity => match resolution {
+            Resolution::Humidity12Temperature14 => HUMIDITY_12_BIT_TIME_MS,
+            Resolution::Humidity8Temperature12 => HUMIDITY_8_BIT_TIME_MS,
+            Resolution::Humidity10Temperature13 => HUMIDITY_10_BIT_TIME_MS,
+            Resolution::Humidity11Temperature11 => HUMIDITY_11_BIT_TIME_MS,
+        },
+    }
+}
+
+impl Resolution {
+    /// Returns the maximum time that the sensor takes to measure the temperature at this
+    /// resolution, in milliseconds
+    ///
+    /// After starting a measurement without clock stretching, waiting this long before
+    /// reading the result avoids polling while the sensor is busy.
+    pub fn max_temperature_time_ms(&self) -> u32 {
+        conversion_time_ms(self, MeasurementType::Temperature)
+    }
+
+    /// Returns the maximum time that the sensor takes to measure the humidity at this
+    /// resolution, in milliseconds
+    ///
+    /// After starting a measurement without clock stretching, waiting this long before
+    /// reading the result avoids polling while the sensor is busy.
+    pub fn max_humidity_time_ms(&self) -> u32 {
+        conversion_time_ms(self, MeasurementType::Humidity)
+    }
+}
+
+/// Number of bytes transferred on the bus to measure one quantity without clock stretching
+///
+/// Writing the command transfers the address and the command byte. Reading the result
+/// transfers the address and three bytes (two data bytes and a CRC byte).
+const READ_TRANSFER_BYTES: u32 = 6;
+
+/// Returns the worst-case time to measure a quantity and read the result, in milliseconds
+///
+/// This is the maximum conversion time (from `conversion_time_ms`) plus the time to transfer
+/// the command and the result on the bus, rounded up to the next millisecond.
+/// `i2c_byte_time_us` is the time to transfer one byte, including the acknowledge bit,
+/// in microseconds. At a 100 kHz clock, this is 90 µs. At 400 kHz, it is 23 µs (rounded up).
+///
+/// The result does not include any delays between transactions or time spent polling for
+/// a result that is not ready.
+pub fn worst_case_read_time_ms(
+    resolution: &Resolution,
+    kind: MeasurementType,
+    i2c_byte_time_us: u32,
+) -> u32 {
+    let transfer_us = READ_TRANSFER_BYTES * i2c_byte_time_us;
+    conversion_time_ms(resolution, kind) + transfer_us.div_ceil(1000)
+}
+
+#[cfg(test)]
+mod timing_test {
+    use super::{conversion_time_ms, worst_case_read_time_ms};
+    use crate::{MeasurementType, Resolution};
+
+    #[test]
+    fn all_conversion_times() {
+        let expected = [
+            (Resolution::Humidity12Temperature14, 50, 16),
+            (Resolution::Humidity8Temperature12, 13, 3),
+            (Resolution::Humidity10Temperature13, 25, 5),
+            (Resolution::Humidity11Temperature11, 7, 8),
+        ];
+        for (resolution, temperature_ms, humidity_ms) in expected.iter() {
+            assert_eq!(
+                conversion_time_ms(resolution, MeasurementType::Temperature),
+                *temperature_ms
+            );
+            assert_eq!(
+                conversion_time_ms(resolution, MeasurementType::Humidity),
+                *humidity_ms
+            );
+            assert_eq!(resolution.max_temperature_time_ms(), *temperature_ms);
+            assert_eq!(resolution.max_humidity_time_ms(), *humidity_ms);
+        }
+    }
+
+    #[test]
+    fn worst_case_read_time() {
+        let resolution = Resolution::Humidity12Temperature14;
+        // 100 kHz: 540 µs of transfers
+        assert_eq!(
+            worst_case_read_time_ms(&resolution, MeasurementType::Temperature, 90),
+            51
+        );
+        // 400 kHz: 138 µs of transfers
+        assert_eq!(
+            worst_case_read_time_ms(&resolution, MeasurementType::Humidity, 23),
+            17
+        );
+        assert_eq!(
+            worst_case_read_time_ms(&resolution, MeasurementType::Humidity, 0),
+            16
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! A driver that keeps track of measurements in progress
+
+use core::marker::PhantomData;
+
+use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
+
+use crate::{Error, Htu2xd, Measurement, MeasurementType, Reading, ResultReader, ADDRESS};
+
+/// HTU2XD driver that keeps track of whether a measurement without clock stretching is in
+/// progress
+///
+/// Starting a measurement while another one is in progress would confuse the sensor. This
+/// driver refuses to do that and returns `Error::MeasurementInProgress` instead.
+///
+/// The `Htu2xd` driver does not keep track of measurements, so it can be used when the
+/// application already prevents this problem.
+pub struct TrackingHtu2xd<I> {
+    driver: Htu2xd<I>,
+    /// The type of measurement in progress, if any
+    pending: Option<MeasurementType>,
+}
+
+impl<I, E> TrackingHtu2xd<I>
+where
+    I: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    /// Creates a driver object, but does not perform any initialization
+    ///
+    /// The driver uses the sensor's default address, 0x40.
+    pub fn new() -> Self {
+        TrackingHtu2xd::new_with_address(ADDRESS)
+    }
+
+    /// Creates a driver object that communicates with a sensor at a non-default I2C address,
+    /// but does not perform any initialization
+    pub fn new_with_address(address: u8) -> Self {
+        TrackingHtu2xd {
+            driver: Htu2xd::new_with_address(address),
+            pending: None,
+        }
+    }
+
+    /// Returns the I2C address that this driver uses
+    pub fn address(&self) -> u8 {
+        self.driver.address()
+    }
+
+    /// Returns true if a measurement has been started and its result has not been read
+    pub fn has_pending_measurement(&self) -> bool {
+        self.pending.is_some()
+    }
+
+    /// Starts measuring temperature or humidity, depending on the type `M`
+    ///
+    /// This function returns `Error::MeasurementInProgress` if another measurement is in
+    /// progress.
+    pub fn start_measurement<M>(&mut self, i2c: &mut I) -> Result<(), Error<E>>
+    where
+        M: Measurement,
+    {
+        if self.pending.is_some() {
+            return Err(Error::MeasurementInProgress);
+        }
+        self.driver.start_measurement::<M>(i2c)?;
+        self.pending = Some(M::TYPE);
+        Ok(())
+    }
+
+    /// Attempts to read the result of the measurement in progress
+    ///
+    /// This works like `ResultReader::read_result`, but returns `Err(nb::Error::WouldBlock)`
+    /// if the measurement is still in progress. When this function returns anything
+    /// other than `Err(nb::Error::WouldBlock)`, the measurement is no longer in progress and
+    /// another measurement can be started.
+    ///
+    /// This function returns `Error::NoMeasurementPending` if no measurement of type `M` is in
+    /// progress.
+    pub fn read_result<M, F>(&mut self, i2c: &mut I, is_nak: F) -> nb::Result<Reading<M>, Error<E>>
+    where
+        M: Measurement,
+        F: FnOnce(&E) -> bool,
+    {
+        if self.pending != Some(M::TYPE) {
+            return Err(nb::Error::Other(Error::NoMeasurementPending));
+        }
+        let mut reader: ResultReader<'_, I, M> = ResultReader {
+            address: self.driver.address,
+            _driver: PhantomData,
+            _reading: PhantomData,
+        };
+        let result = reader.poll_result(i2c, is_nak);
+        if !matches!(result, Err(nb::Error::WouldBlock)) {
+            self.pending = None;
+        }
+        result
+    }
+}
+
+impl<I, E> Default for TrackingHtu2xd<I>
+where
+    I: Read<Error = E> + Write<Error = E> + WriteRead<Error = E>,
+{
+    fn default() -> Self {
+        TrackingHtu2xd::new()
+    }
+}
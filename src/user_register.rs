@@ -3,7 +3,9 @@
 /// Resolution for temperature and humidity measurements
 ///
 /// Lower resolutions take less time to measure.
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Resolution {
     /// 12-bit humidity, 14-bit temperature
     Humidity12Temperature14,
@@ -15,10 +17,33 @@ pub enum Resolution {
     Humidity11Temperature11,
 }
 
+impl Resolution {
+    /// Returns the number of bits in a temperature measurement at this resolution
+    pub(crate) fn temperature_bits(&self) -> u32 {
+        match self {
+            Resolution::Humidity12Temperature14 => 14,
+            Resolution::Humidity8Temperature12 => 12,
+            Resolution::Humidity10Temperature13 => 13,
+            Resolution::Humidity11Temperature11 => 11,
+        }
+    }
+    /// Returns the number of bits in a humidity measurement at this resolution
+    pub(crate) fn humidity_bits(&self) -> u32 {
+        match self {
+            Resolution::Humidity12Temperature14 => 12,
+            Resolution::Humidity8Temperature12 => 8,
+            Resolution::Humidity10Temperature13 => 10,
+            Resolution::Humidity11Temperature11 => 11,
+        }
+    }
+}
+
 /// Measurement of the power supply voltage
 ///
 /// Note: The sensor's minimum power supply voltage is 1.5 V.
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum SupplyVoltage {
     /// Greater than 2.25 +/- 0.1 V
     High,
@@ -26,14 +51,68 @@ pub enum SupplyVoltage {
     Low,
 }
 
+/// The configurable settings in the user register
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub struct Configuration {
+    /// Measurement resolution
+    pub resolution: Resolution,
+    /// On-chip heater enabled
+    pub heater_enabled: bool,
+    /// Default settings reloaded from one-time programmable memory after each measurement
+    pub otp_reload_enabled: bool,
+}
+
+impl Default for Configuration {
+    /// Returns the settings that the sensor uses after power-on or a soft reset
+    fn default() -> Self {
+        Configuration {
+            resolution: Resolution::Humidity12Temperature14,
+            heater_enabled: false,
+            otp_reload_enabled: false,
+        }
+    }
+}
+
+/// An error found when checking a user register value
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+pub enum RegisterError {
+    /// The reserved bits (3, 4, and 5) were neither all set nor all clear
+    ReservedBits,
+}
+
+/// Mask for the reserved bits 3, 4, and 5
+const RESERVED_MASK: u8 = 0b0011_1000;
+
 /// The user register, used for configuration
 ///
-/// The only way to create a `UserRegister` object is to read it from a sensor. It can then be
-/// modified and written back.
+/// A `UserRegister` object is normally read from a sensor. It can then be modified and
+/// written back.
 // The enclosed value is represented exactly as the sensor sends and receives it.
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
 pub struct UserRegister(pub(crate) u8);
 
 impl UserRegister {
+    /// Creates a register from a value exactly as the sensor sends and receives it
+    ///
+    /// The reserved bits must have the same values that the sensor reported. Reading the
+    /// register from the sensor is the safest way to get a correct value.
+    pub fn from_raw(value: u8) -> Self {
+        UserRegister(value)
+    }
+
+    /// Checks that the reserved bits have a plausible value
+    ///
+    /// Sensors report the reserved bits as either all set or all clear, depending on the
+    /// manufacturer. Any other combination indicates that the value is corrupted.
+    pub fn validate(&self) -> Result<(), RegisterError> {
+        match self.0 & RESERVED_MASK {
+            0 | RESERVED_MASK => Ok(()),
+            _ => Err(RegisterError::ReservedBits),
+        }
+    }
+
     /// Returns the current measurement resolution
     pub fn resolution(&self) -> Resolution {
         let bit7 = ((self.0 >> 7) & 1) == 1;
@@ -54,6 +133,13 @@ impl UserRegister {
             SupplyVoltage::High
         }
     }
+    /// Returns true if the supply voltage was above the low-voltage threshold when the last
+    /// temperature or humidity measurement was taken
+    ///
+    /// This is equivalent to checking that `supply_voltage()` returns `SupplyVoltage::High`.
+    pub fn battery_ok(&self) -> bool {
+        matches!(self.supply_voltage(), SupplyVoltage::High)
+    }
     /// Returns true if the on-chip heater is enabled
     pub fn heater_enabled(&self) -> bool {
         ((self.0 >> 2) & 1) == 1
@@ -68,6 +154,22 @@ impl UserRegister {
         !bit1
     }
 
+    /// Returns the configurable settings in this register
+    pub fn configuration(&self) -> Configuration {
+        Configuration {
+            resolution: self.resolution(),
+            heater_enabled: self.heater_enabled(),
+            otp_reload_enabled: self.otp_reload_enabled(),
+        }
+    }
+
+    /// Returns true if the configurable settings in this register match a configuration
+    ///
+    /// The reserved bits and the supply voltage bit are not compared.
+    pub fn matches_config(&self, configuration: &Configuration) -> bool {
+        self.configuration() == *configuration
+    }
+
     ///  Sets the measurement resolution
     pub fn set_resolution(&mut self, resolution: Resolution) {
         let bit_7_mask = 1u8 << 7;
@@ -111,6 +213,75 @@ impl UserRegister {
             self.0 |= bit_1_mask;
         }
     }
+    /// Returns this register with a different measurement resolution
+    pub fn with_resolution(mut self, resolution: Resolution) -> Self {
+        self.set_resolution(resolution);
+        self
+    }
+    /// Returns this register with the on-chip heater enabled or disabled
+    pub fn with_heater_enabled(mut self, enabled: bool) -> Self {
+        self.set_heater_enabled(enabled);
+        self
+    }
+    /// Returns this register with the reloading of default settings from one-time
+    /// programmable memory enabled or disabled
+    pub fn with_otp_reload_enabled(mut self, otp_reload: bool) -> Self {
+        self.set_otp_reload_enabled(otp_reload);
+        self
+    }
+    /// Changes all configurable settings to match a configuration, leaving the reserved bits
+    /// unchanged
+    pub fn set_configuration(&mut self, configuration: &Configuration) {
+        self.set_resolution(configuration.resolution.clone());
+        self.set_heater_enabled(configuration.heater_enabled);
+        self.set_otp_reload_enabled(configuration.otp_reload_enabled);
+    }
+
+    /// Returns a human-readable summary of this register
+    ///
+    /// The summary is the same text that the `Display` implementation writes, for example
+    /// `RH12/T14, heater off, OTP reload off, battery OK`.
+    ///
+    /// This function requires the `alloc` feature.
+    #[cfg(feature = "alloc")]
+    pub fn summary(&self) -> alloc::string::String {
+        use alloc::string::ToString;
+        self.to_string()
+    }
+}
+
+mod display_impl {
+    use super::{Resolution, UserRegister};
+    use core::fmt::{Display, Formatter, Result};
+
+    fn on_off(enabled: bool) -> &'static str {
+        if enabled {
+            "on"
+        } else {
+            "off"
+        }
+    }
+
+    impl Display for UserRegister {
+        /// Writes a summary like `RH12/T14, heater off, OTP reload off, battery OK`
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+            write!(
+                f,
+                "{}, heater {}, OTP reload {}, battery {}",
+                self.resolution(),
+                on_off(self.heater_enabled()),
+                on_off(self.otp_reload_enabled()),
+                if self.battery_ok() { "OK" } else { "low" }
+            )
+        }
+    }
+
+    impl Display for Resolution {
+        /// Writes the resolution like `RH12/T14`
+        fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+            write!(f, "RH{}/T{}", self.humidity_bits(), self.temperature_bits())
+        }
+    }
 }
 
 mod debug_impl {
@@ -128,3 +299,23 @@ mod debug_impl {
         }
     }
 }
+
+#[cfg(test)]
+mod user_register_test {
+    extern crate std;
+
+    use super::UserRegister;
+    use std::string::ToString;
+
+    #[test]
+    fn display() {
+        assert_eq!(
+            UserRegister(0b0000_0010).to_string(),
+            "RH12/T14, heater off, OTP reload off, battery OK"
+        );
+        assert_eq!(
+            UserRegister(0b1111_1101).to_string(),
+            "RH11/T11, heater on, OTP reload on, battery low"
+        );
+    }
+}
@@ -1,13 +1,26 @@
+#![cfg(feature = "hal-02")]
+
 extern crate embedded_hal;
 extern crate embedded_hal_mock;
 extern crate htu2xd;
 extern crate nb;
 
+use std::convert::TryFrom;
 use std::io;
+use std::task::Poll;
 
+use embedded_hal::blocking::delay::DelayMs;
+use embedded_hal::blocking::i2c::{Read, Write, WriteRead};
+use embedded_hal_mock::delay::MockNoop;
 use embedded_hal_mock::i2c::{Mock, Transaction};
 use embedded_hal_mock::MockError;
-use htu2xd::{Htu2xd, Reading, Resolution, SupplyVoltage};
+use htu2xd::{
+    parse_reading_with_crc, Bound, ComfortZone, Configuration, Crc, CrcError, Diagnosis,
+    FirmwareRevision, FrameError, Htu2xd, Htu2xdOwned, Humidity, Measurement, MeasurementType,
+    NakError, PollConfig, ReadAttempt, Reading, ReadingDiagnosis, ReadingError, RegisterError,
+    Resolution, SupplyVoltage, Temperature, TrackingHtu2xd, UserRegister, HEATER_COOLDOWN_MS,
+    HEATER_PULSE_MS,
+};
 
 /// Address of the sensor
 const ADDRESS: u8 = 0x40;
@@ -35,8 +48,8 @@ fn user_register() -> Result<(), Box<dyn std::error::Error>> {
         Resolution::Humidity12Temperature14
     ));
     assert!(matches!(register.supply_voltage(), SupplyVoltage::High));
-    assert_eq!(register.otp_reload_enabled(), false);
-    assert_eq!(register.heater_enabled(), false);
+    assert!(!register.otp_reload_enabled());
+    assert!(!register.heater_enabled());
 
     // Change everything that can be changed
     register.set_resolution(Resolution::Humidity11Temperature11);
@@ -47,8 +60,8 @@ fn user_register() -> Result<(), Box<dyn std::error::Error>> {
         register.resolution(),
         Resolution::Humidity11Temperature11
     ));
-    assert_eq!(register.otp_reload_enabled(), true);
-    assert_eq!(register.heater_enabled(), true);
+    assert!(register.otp_reload_enabled());
+    assert!(register.heater_enabled());
     // Write changes back
     htu.write_user_register(&mut mock, register)?;
 
@@ -56,6 +69,329 @@ fn user_register() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+#[test]
+fn battery_ok() {
+    let expected = [
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0b0011_1010]),
+        // End of battery
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0b0111_1010]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    assert!(htu.read_user_register(&mut mock).unwrap().battery_ok());
+    assert!(!htu.read_user_register(&mut mock).unwrap().battery_ok());
+
+    mock.done();
+}
+
+#[test]
+fn user_register_matches_config() {
+    let expected = [Transaction::write_read(
+        ADDRESS,
+        vec![0xe7],
+        vec![0b0011_1010],
+    )];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let mut register = htu.read_user_register(&mut mock).unwrap();
+    assert!(register.matches_config(&Configuration::default()));
+
+    register.set_resolution(Resolution::Humidity11Temperature11);
+    register.set_heater_enabled(true);
+    register.set_otp_reload_enabled(true);
+    assert!(!register.matches_config(&Configuration::default()));
+    assert!(register.matches_config(&Configuration {
+        resolution: Resolution::Humidity11Temperature11,
+        heater_enabled: true,
+        otp_reload_enabled: true,
+    }));
+
+    mock.done();
+}
+
+#[test]
+fn soft_reset_verified() {
+    let expected = [
+        Transaction::write(ADDRESS, vec![0xfe]),
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0b0011_1010]),
+        // Sensor does not respond after the second reset
+        Transaction::write(ADDRESS, vec![0xfe]),
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0])
+            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
+        // Resolution was not reset
+        Transaction::write(ADDRESS, vec![0xfe]),
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0b1011_1010]),
+    ];
+    let mut mock = Mock::new(&expected);
+    let mut delay = MockNoop::new();
+
+    let mut htu = Htu2xd::new();
+    assert!(htu.soft_reset_verified(&mut mock, &mut delay).is_ok());
+    for _ in 0..2 {
+        let error = htu.soft_reset_verified(&mut mock, &mut delay).unwrap_err();
+        assert!(matches!(error, htu2xd::Error::ResetFailed));
+    }
+
+    mock.done();
+}
+
+#[test]
+fn configure_and_read_back() -> Result<(), Box<dyn std::error::Error>> {
+    let expected = [
+        Transaction::write(ADDRESS, vec![0xfe]),
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0b0011_1010]),
+        Transaction::write(ADDRESS, vec![0xe6, 0b1011_1110]),
+        // The sensor did not accept the heater setting
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0b1011_1010]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let requested = Configuration {
+        resolution: Resolution::Humidity10Temperature13,
+        heater_enabled: true,
+        otp_reload_enabled: false,
+    };
+    let mut htu = Htu2xd::new();
+    let applied = htu
+        .configure_and_read_back(&mut mock, &mut MockNoop::new(), requested.clone())
+        .map_err(|_| "configure failed")?;
+    assert_eq!(applied.resolution, requested.resolution);
+    assert!(!applied.heater_enabled);
+    assert!(!applied.otp_reload_enabled);
+
+    mock.done();
+    Ok(())
+}
+
+#[test]
+fn user_register_validate() {
+    assert_eq!(UserRegister::from_raw(0b0011_1010).validate(), Ok(()));
+    assert_eq!(UserRegister::from_raw(0b0000_0010).validate(), Ok(()));
+    assert_eq!(
+        UserRegister::from_raw(0b0001_0010).validate(),
+        Err(RegisterError::ReservedBits)
+    );
+}
+
+#[test]
+fn user_register_builder() {
+    let register = UserRegister::from_raw(0b0011_1010)
+        .with_resolution(Resolution::Humidity8Temperature12)
+        .with_heater_enabled(true)
+        .with_otp_reload_enabled(true);
+    assert!(register.matches_config(&Configuration {
+        resolution: Resolution::Humidity8Temperature12,
+        heater_enabled: true,
+        otp_reload_enabled: true,
+    }));
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic]
+fn write_invalid_user_register() {
+    let mut mock = Mock::new(&[]);
+    let mut htu = Htu2xd::new();
+    let _ = htu.write_user_register(&mut mock, UserRegister::from_raw(0b0010_0010));
+}
+
+#[test]
+fn user_register_raw() {
+    let expected = [Transaction::write_read(
+        ADDRESS,
+        vec![0xe7],
+        vec![0b0011_1010],
+    )];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    assert_eq!(htu.read_user_register_raw(&mut mock).unwrap(), 0b0011_1010);
+
+    mock.done();
+}
+
+#[test]
+fn serial_number() {
+    let expected = [
+        Transaction::write_read(
+            ADDRESS,
+            vec![0xfa, 0x0f],
+            vec![0x01, 0x31, 0x5b, 0x60, 0x3c, 0x03, 0x6e, 0xf7],
+        ),
+        Transaction::write_read(
+            ADDRESS,
+            vec![0xfc, 0xc9],
+            vec![0x32, 0x15, 0xdd, 0x48, 0x54, 0x80],
+        ),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let serial = htu.read_serial_number(&mut mock).unwrap();
+    assert_eq!(serial, [0x48, 0x54, 0x01, 0x5b, 0x3c, 0x6e, 0x32, 0x15]);
+
+    mock.done();
+}
+
+#[test]
+fn serial_number_corrupted_crc() {
+    // The last CRC byte is incorrect
+    let expected = [
+        Transaction::write_read(
+            ADDRESS,
+            vec![0xfa, 0x0f],
+            vec![0x01, 0x31, 0x5b, 0x60, 0x3c, 0x03, 0x6e, 0xf7],
+        ),
+        Transaction::write_read(
+            ADDRESS,
+            vec![0xfc, 0xc9],
+            vec![0x32, 0x15, 0xdd, 0x48, 0x54, 0x81],
+        ),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let error = htu.read_serial_number(&mut mock).unwrap_err();
+    assert!(matches!(
+        error,
+        htu2xd::Error::CrcMismatch {
+            computed: 0x80,
+            expected: 0x81,
+        }
+    ));
+
+    mock.done();
+}
+
+#[test]
+fn serial_number_chained_crc() {
+    // Each CRC byte here only covers the byte immediately before it. That would be correct if
+    // the CRCs were independent, but the sensor chains them.
+    let expected = [Transaction::write_read(
+        ADDRESS,
+        vec![0xfa, 0x0f],
+        vec![0x01, 0x31, 0x5b, 0x94, 0x3c, 0xb8, 0x6e, 0xa4],
+    )];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let error = htu.read_serial_number(&mut mock).unwrap_err();
+    assert!(matches!(
+        error,
+        htu2xd::Error::CrcMismatch {
+            computed: 0x60,
+            expected: 0x94,
+        }
+    ));
+
+    mock.done();
+}
+
+#[test]
+fn firmware_revision() {
+    let expected = [
+        Transaction::write_read(ADDRESS, vec![0x84, 0xb8], vec![0xff]),
+        Transaction::write_read(ADDRESS, vec![0x84, 0xb8], vec![0x20]),
+        Transaction::write_read(ADDRESS, vec![0x84, 0xb8], vec![0x31]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let revision = htu.read_firmware_revision(&mut mock).unwrap();
+    assert_eq!(revision, 0xff);
+    assert_eq!(FirmwareRevision::from(revision), FirmwareRevision::V1_0);
+    let revision = htu.read_firmware_revision(&mut mock).unwrap();
+    assert_eq!(FirmwareRevision::from(revision), FirmwareRevision::V2_0);
+    let revision = htu.read_firmware_revision(&mut mock).unwrap();
+    assert_eq!(
+        FirmwareRevision::from(revision),
+        FirmwareRevision::Unknown(0x31)
+    );
+
+    mock.done();
+}
+
+#[test]
+fn resolution() {
+    let expected = [Transaction::write_read(
+        ADDRESS,
+        vec![0xe7],
+        vec![0b1011_1011],
+    )];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    assert_eq!(
+        htu.read_resolution(&mut mock).unwrap(),
+        Resolution::Humidity11Temperature11
+    );
+
+    mock.done();
+}
+
+#[test]
+fn user_register_batch() {
+    let expected = [
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0x3a]),
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0x3a]),
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0x7a]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let values = htu.read_user_register_batch::<3>(&mut mock).unwrap();
+    assert_eq!(values, [0x3a, 0x3a, 0x7a]);
+
+    mock.done();
+}
+
+#[test]
+fn raw_command() {
+    let expected = [
+        Transaction::write_read(ADDRESS, vec![0xe0], vec![0x4e, 0x85, 0x6b]),
+        Transaction::write(ADDRESS, vec![0xfe]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let mut buffer = [0u8; 4];
+    htu.read_raw_command(&mut mock, 0xe0, 3, &mut buffer)
+        .unwrap();
+    assert_eq!(buffer, [0x4e, 0x85, 0x6b, 0x00]);
+    htu.read_raw_command(&mut mock, 0xfe, 0, &mut []).unwrap();
+
+    mock.done();
+}
+
+#[test]
+fn bus_health_check() {
+    let expected = [
+        // Unconfirmed reads, starting with a corrupted read
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0x3b]),
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0x3a]),
+        // Confirms the reference value
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0x3a]),
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0x3a]),
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0x3a])
+            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0x3a]),
+        // A bit flipped on the bus
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0x3b]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let health = htu.bus_health_check(&mut mock, 7);
+    assert_eq!(health.ok, 3);
+    assert_eq!(health.crc_errors, 1);
+    assert_eq!(health.i2c_errors, 1);
+    assert_eq!(health.unconfirmed, 2);
+
+    mock.done();
+}
+
 #[test]
 fn temperature_humidity_clock_stretch() {
     let expected = [
@@ -92,76 +428,1269 @@ fn temperature_humidity_clock_stretch() {
 }
 
 #[test]
-fn temperature_humidity_nak() {
-    /// A ConnectionRefused error here represents a NAK
-    fn is_nak(error: &MockError) -> bool {
-        matches!(error, MockError::Io(io::ErrorKind::ConnectionRefused))
-    }
-
+fn temperature_humidity_clock_stretch_separate() {
     let expected = [
-        // Start temperature read
-        Transaction::write(ADDRESS, vec![0xf3]),
-        // Several read attempts that return NAK
-        Transaction::read(ADDRESS, vec![0u8; 3])
-            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
-        Transaction::read(ADDRESS, vec![0u8; 3])
-            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
-        Transaction::read(ADDRESS, vec![0u8; 3])
-            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
-        Transaction::read(ADDRESS, vec![0u8; 3])
-            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
-        // Done measuring, return results
+        Transaction::write(ADDRESS, vec![0xe3]),
         Transaction::read(ADDRESS, vec![0x4e, 0x85, 0x6b]),
-        // Start humidity read
-        Transaction::write(ADDRESS, vec![0xf5]),
-        // Several read attempts that return NAK
-        Transaction::read(ADDRESS, vec![0u8; 3])
-            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
-        Transaction::read(ADDRESS, vec![0u8; 3])
-            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
-        // Done measuring, return results
+        Transaction::write(ADDRESS, vec![0xe5]),
         Transaction::read(ADDRESS, vec![0x68, 0x3a, 0x7c]),
     ];
     let mut mock = Mock::new(&expected);
 
     let mut htu = Htu2xd::new();
-
-    let mut temperature_step2 = htu.read_temperature(&mut mock).unwrap();
-    // 4 NAK errors while the sensor is measuring
-    for _ in 0..4 {
-        let error = temperature_step2
-            .read_result(&mut mock, is_nak)
-            .unwrap_err();
-        assert!(matches!(error, nb::Error::WouldBlock));
+    match htu.read_temperature_blocking_separate(&mut mock).unwrap() {
+        Reading::Ok(reading) => assert_eq!(reading.as_raw(), 0x4e84),
+        _ => panic!("Unexpected error"),
     }
-    // Now the sensor is done
-    match temperature_step2.read_result(&mut mock, is_nak).unwrap() {
-        Reading::Ok(reading) => {
-            assert_eq!(reading.as_raw(), 0x4e84);
-            let degrees_c = reading.as_degrees_celsius();
-            assert!(degrees_c >= 7.04);
-            assert!(degrees_c < 7.05);
-        }
-        Reading::ErrorLow => panic!("Unexpected error low"),
-        Reading::ErrorHigh => panic!("Unexpected error high"),
+    match htu.read_humidity_blocking_separate(&mut mock).unwrap() {
+        Reading::Ok(reading) => assert_eq!(reading.as_raw(), 0x6838),
+        _ => panic!("Unexpected error"),
     }
-    let mut humidity_step2 = htu.read_humidity(&mut mock).unwrap();
-    // 2 NAK errors while the sensor is measuring
-    for _ in 0..2 {
-        let error = humidity_step2.read_result(&mut mock, is_nak).unwrap_err();
-        assert!(matches!(error, nb::Error::WouldBlock));
+
+    mock.done();
+}
+
+#[test]
+fn temperature_humidity_clock_stretch_into() {
+    let expected = [
+        Transaction::write_read(ADDRESS, vec![0xe3], vec![0x4e, 0x85, 0x6b]),
+        Transaction::write_read(ADDRESS, vec![0xe5], vec![0x68, 0x3a, 0x7c]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut buffer = [0u8; 4];
+    let mut htu = Htu2xd::new();
+    match htu
+        .read_temperature_blocking_into(&mut mock, &mut buffer)
+        .unwrap()
+    {
+        Reading::Ok(reading) => assert_eq!(reading.as_raw(), 0x4e84),
+        _ => panic!("Unexpected error"),
     }
-    // Now the sensor is done
-    match humidity_step2.read_result(&mut mock, is_nak).unwrap() {
-        Reading::Ok(reading) => {
-            assert_eq!(reading.as_raw(), 0x6838);
-            let percent = reading.as_percent_relative();
-            assert!(percent >= 44.8);
-            assert!(percent < 44.9);
-        }
-        Reading::ErrorLow => panic!("Unexpected error low"),
-        Reading::ErrorHigh => panic!("Unexpected error high"),
+    assert_eq!(buffer, [0x4e, 0x85, 0x6b, 0x00]);
+    match htu
+        .read_humidity_blocking_into(&mut mock, &mut buffer)
+        .unwrap()
+    {
+        Reading::Ok(reading) => assert_eq!(reading.as_raw(), 0x6838),
+        _ => panic!("Unexpected error"),
     }
 
     mock.done();
 }
+
+#[test]
+fn temperature_crc_mismatch() {
+    let expected = [Transaction::write_read(
+        ADDRESS,
+        vec![0xe3],
+        vec![0x4e, 0x85, 0x6a],
+    )];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let error = htu.read_temperature_blocking(&mut mock).unwrap_err();
+    assert!(matches!(
+        error,
+        htu2xd::Error::Crc {
+            received: [0x4e, 0x85, 0x6a],
+            computed: 0x6b,
+        }
+    ));
+
+    mock.done();
+}
+
+#[test]
+fn temperature_humidity_value_clock_stretch() {
+    let expected = [
+        Transaction::write_read(ADDRESS, vec![0xe3], vec![0x4e, 0x85, 0x6b]),
+        // Off-scale high
+        Transaction::write_read(ADDRESS, vec![0xe5], vec![0xff, 0xff, 0x2d]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let temperature = htu.read_temperature_value_blocking(&mut mock).unwrap();
+    assert_eq!(temperature.as_raw(), 0x4e84);
+    let error = htu.read_humidity_value_blocking(&mut mock).unwrap_err();
+    assert!(matches!(error, htu2xd::Error::OffScale(Bound::High)));
+
+    mock.done();
+}
+
+#[test]
+fn reading_into_result() {
+    let expected = [
+        Transaction::write_read(ADDRESS, vec![0xe5], vec![0x68, 0x3a, 0x7c]),
+        // Off-scale high
+        Transaction::write_read(ADDRESS, vec![0xe5], vec![0xff, 0xff, 0x2d]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let humidity = htu.read_humidity_blocking(&mut mock).unwrap();
+    assert_eq!(humidity.into_result().unwrap().as_raw(), 0x6838);
+    let humidity = htu.read_humidity_blocking(&mut mock).unwrap();
+    assert_eq!(
+        humidity.into_result().unwrap_err(),
+        ReadingError::OffScaleHigh
+    );
+    let low: Reading<Humidity> = Reading::ErrorLow;
+    assert_eq!(low.into_result().unwrap_err(), ReadingError::OffScaleLow);
+
+    mock.done();
+}
+
+#[test]
+fn humidity_diagnose() {
+    let expected = [
+        // Off-scale high
+        Transaction::write_read(ADDRESS, vec![0xe5], vec![0xff, 0xff, 0x2d]),
+        // 111% relative humidity
+        Transaction::write_read(ADDRESS, vec![0xe5], vec![0xf0, 0x02, 0x7a]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let humidity = htu.read_humidity_blocking(&mut mock).unwrap();
+    assert!(humidity.is_likely_short_circuit());
+    assert!(!humidity.is_likely_open_circuit());
+    assert_eq!(
+        humidity.diagnose(None),
+        ReadingDiagnosis::LikelyShortCircuit
+    );
+
+    // An off-scale reading after a reading near the top of the range
+    let previous = htu.read_humidity_value_blocking(&mut mock).unwrap();
+    assert_eq!(
+        humidity.diagnose(Some(&previous)),
+        ReadingDiagnosis::LikelyExtremeHigh
+    );
+
+    mock.done();
+}
+
+#[test]
+fn temperature_at_resolution() {
+    let expected = [Transaction::write_read(
+        ADDRESS,
+        vec![0xe3],
+        vec![0x4e, 0x85, 0x6b],
+    )];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let temperature = htu.read_temperature_value_blocking(&mut mock).unwrap();
+    // Same as the full conversion at the highest resolution
+    assert_eq!(
+        temperature.as_degrees_celsius_at(&Resolution::Humidity12Temperature14),
+        temperature.as_degrees_celsius()
+    );
+    // 0x4e84 rounds to 0x4e80 at 11 bits
+    let rounded = temperature.as_degrees_celsius_at(&Resolution::Humidity11Temperature11);
+    assert!(rounded >= 7.03);
+    assert!(rounded < 7.04);
+
+    mock.done();
+}
+
+#[test]
+fn humidity_into_f32_or_nan() {
+    let expected = [
+        Transaction::write_read(ADDRESS, vec![0xe5], vec![0x68, 0x3a, 0x7c]),
+        // Off-scale high
+        Transaction::write_read(ADDRESS, vec![0xe5], vec![0xff, 0xff, 0x2d]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let humidity = htu.read_humidity_blocking(&mut mock).unwrap();
+    let percent = humidity.into_f32_or_nan(|h| h.as_percent_relative());
+    assert!(percent >= 44.8);
+    assert!(percent < 44.9);
+    let humidity = htu.read_humidity_blocking(&mut mock).unwrap();
+    assert!(humidity
+        .into_f32_or_nan(|h| h.as_percent_relative())
+        .is_nan());
+
+    mock.done();
+}
+
+#[test]
+fn comfort() {
+    let expected = [
+        Transaction::write(ADDRESS, vec![0xf3]),
+        Transaction::read(ADDRESS, vec![0x4e, 0x85, 0x6b]),
+        Transaction::write(ADDRESS, vec![0xf5]),
+        Transaction::read(ADDRESS, vec![0x68, 0x3a, 0x7c]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let (temperature, humidity, zone) = htu.read_comfort(&mut mock, &mut MockNoop::new()).unwrap();
+    assert_eq!(temperature.as_raw(), 0x4e84);
+    assert_eq!(humidity.as_raw(), 0x6838);
+    // 7.04 degrees C
+    assert_eq!(zone, ComfortZone::Cold);
+
+    mock.done();
+}
+
+#[test]
+fn humidity_supersaturated() {
+    let expected = [Transaction::write_read(
+        ADDRESS,
+        vec![0xe5],
+        vec![0xf0, 0x02, 0x7a],
+    )];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let humidity = htu.read_humidity_value_blocking(&mut mock).unwrap();
+    assert_eq!(humidity.as_percent_relative(), 100.0);
+    let unclamped = humidity.as_percent_relative_unclamped();
+    assert!(unclamped >= 111.1);
+    assert!(unclamped < 111.2);
+
+    mock.done();
+}
+
+/// A delay that records the requested times instead of waiting
+///
+/// `MockNoop` does not check the times, so this is used where the delays matter.
+struct RecordingDelay(Vec<u32>);
+
+impl DelayMs<u32> for RecordingDelay {
+    fn delay_ms(&mut self, ms: u32) {
+        self.0.push(ms);
+    }
+}
+
+#[test]
+fn climate_auto_heater() {
+    let expected = [
+        Transaction::write(ADDRESS, vec![0xf3]),
+        Transaction::read(ADDRESS, vec![0x4e, 0x85, 0x6b]),
+        Transaction::write(ADDRESS, vec![0xf5]),
+        // 111% relative humidity
+        Transaction::read(ADDRESS, vec![0xf0, 0x02, 0x7a]),
+        // Heater on, then restored
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0b0011_1010]),
+        Transaction::write(ADDRESS, vec![0xe6, 0b0011_1110]),
+        Transaction::write(ADDRESS, vec![0xe6, 0b0011_1010]),
+        // Measure again
+        Transaction::write(ADDRESS, vec![0xf3]),
+        Transaction::read(ADDRESS, vec![0x4e, 0x85, 0x6b]),
+        Transaction::write(ADDRESS, vec![0xf5]),
+        Transaction::read(ADDRESS, vec![0x68, 0x3a, 0x7c]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let mut delay = RecordingDelay(Vec::new());
+    let (_, humidity) = htu
+        .read_climate_auto_heater(&mut mock, &mut delay, 95.0)
+        .unwrap();
+    assert_eq!(humidity.as_raw(), 0x6838);
+    // Conversions, heater pulse, cooldown, and conversions again
+    assert_eq!(
+        delay.0,
+        [50, 16, HEATER_PULSE_MS, HEATER_COOLDOWN_MS, 50, 16]
+    );
+
+    mock.done();
+}
+
+#[test]
+fn climate_with_resolutions() {
+    let expected = [
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0b0011_1010]),
+        // 14-bit temperature
+        Transaction::write(ADDRESS, vec![0xe6, 0b0011_1010]),
+        Transaction::write(ADDRESS, vec![0xf3]),
+        Transaction::read(ADDRESS, vec![0x4e, 0x85, 0x6b]),
+        // 8-bit humidity
+        Transaction::write(ADDRESS, vec![0xe6, 0b0011_1011]),
+        Transaction::write(ADDRESS, vec![0xf5]),
+        Transaction::read(ADDRESS, vec![0x68, 0x3a, 0x7c]),
+        // Restored
+        Transaction::write(ADDRESS, vec![0xe6, 0b0011_1010]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let (temperature, humidity) = htu
+        .read_with_resolutions(
+            &mut mock,
+            &mut MockNoop::new(),
+            Resolution::Humidity12Temperature14,
+            Resolution::Humidity8Temperature12,
+        )
+        .unwrap();
+    assert_eq!(temperature.as_raw(), 0x4e84);
+    assert_eq!(humidity.as_raw(), 0x6838);
+
+    mock.done();
+}
+
+#[test]
+fn user_register_with_crc() {
+    let expected = [
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0x02, 0x62]),
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0x02, 0x63]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let register = htu.read_user_register_with_crc(&mut mock).unwrap();
+    assert_eq!(register.resolution(), Resolution::Humidity12Temperature14);
+    assert!(!register.otp_reload_enabled());
+    assert!(matches!(
+        htu.read_user_register_with_crc(&mut mock),
+        Err(htu2xd::Error::CrcMismatch {
+            computed: 0x62,
+            expected: 0x63,
+        })
+    ));
+
+    mock.done();
+}
+
+#[test]
+fn blocking_timeout() {
+    /// A TimedOut error here represents an I2C driver timeout
+    fn is_timeout(error: &MockError) -> bool {
+        matches!(error, MockError::Io(io::ErrorKind::TimedOut))
+    }
+
+    let expected = [
+        Transaction::write_read(ADDRESS, vec![0xe3], vec![0x4e, 0x85, 0x6b]),
+        Transaction::write_read(ADDRESS, vec![0xe5], vec![0u8; 3])
+            .with_error(MockError::Io(io::ErrorKind::TimedOut)),
+        Transaction::write_read(ADDRESS, vec![0xe5], vec![0u8; 3])
+            .with_error(MockError::Io(io::ErrorKind::Other)),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let temperature = htu
+        .read_temperature_blocking_timeout(&mut mock, is_timeout)
+        .unwrap();
+    assert!(temperature.is_ok());
+    assert!(matches!(
+        htu.read_humidity_blocking_timeout(&mut mock, is_timeout),
+        Err(htu2xd::Error::Timeout)
+    ));
+    assert!(matches!(
+        htu.read_humidity_blocking_timeout(&mut mock, is_timeout),
+        Err(htu2xd::Error::I2c(_))
+    ));
+
+    mock.done();
+}
+
+#[test]
+fn characterize() {
+    let mut expected = vec![Transaction::write_read(
+        ADDRESS,
+        vec![0xe7],
+        vec![0b0011_1010],
+    )];
+    for register in [0b0011_1010, 0b0011_1011, 0b1011_1010, 0b1011_1011].iter() {
+        expected.extend_from_slice(&[
+            Transaction::write(ADDRESS, vec![0xe6, *register]),
+            Transaction::write(ADDRESS, vec![0xf3]),
+            Transaction::read(ADDRESS, vec![0x4e, 0x85, 0x6b]),
+            Transaction::write(ADDRESS, vec![0xf5]),
+            Transaction::read(ADDRESS, vec![0x68, 0x3a, 0x7c]),
+        ]);
+    }
+    expected.push(Transaction::write(ADDRESS, vec![0xe6, 0b0011_1010]));
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let reports = htu.characterize(&mut mock, &mut MockNoop::new()).unwrap();
+    assert_eq!(reports[1].resolution, Resolution::Humidity8Temperature12);
+    assert_eq!(reports[1].temperature_time_ms, 13);
+    assert_eq!(reports[1].humidity_time_ms, 3);
+    assert!(reports.iter().all(|report| report.temperature.is_ok()));
+    assert!(reports.iter().all(|report| report.humidity.is_ok()));
+
+    mock.done();
+}
+
+#[test]
+fn persistent_resolution() {
+    let expected = [
+        // OTP reload enabled
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0b0011_1000]),
+        // 8-bit humidity, 12-bit temperature, OTP reload disabled
+        Transaction::write(ADDRESS, vec![0xe6, 0b0011_1011]),
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0b0011_1000]),
+        // Default resolution, OTP reload not changed
+        Transaction::write(ADDRESS, vec![0xe6, 0b0011_1000]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    htu.set_persistent_resolution(&mut mock, Resolution::Humidity8Temperature12)
+        .unwrap();
+    htu.set_persistent_resolution(&mut mock, Resolution::Humidity12Temperature14)
+        .unwrap();
+
+    mock.done();
+}
+
+#[test]
+fn self_diagnose() {
+    let expected = [
+        Transaction::write_read(ADDRESS, vec![0xe3], vec![0x4e, 0x85, 0x6b]),
+        Transaction::write_read(ADDRESS, vec![0xe5], vec![0x68, 0x3a, 0x7c]),
+        // Short circuit
+        Transaction::write_read(ADDRESS, vec![0xe3], vec![0xff, 0xff, 0x2d]),
+        Transaction::write_read(ADDRESS, vec![0xe5], vec![0x68, 0x3a, 0x7c]),
+        // Short circuit and CRC failure
+        Transaction::write_read(ADDRESS, vec![0xe3], vec![0xff, 0xff, 0x2d]),
+        Transaction::write_read(ADDRESS, vec![0xe5], vec![0x68, 0x3a, 0x7d]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    assert_eq!(htu.self_diagnose(&mut mock).unwrap(), Diagnosis::Ok);
+    assert_eq!(
+        htu.self_diagnose(&mut mock).unwrap(),
+        Diagnosis::ShortCircuitSuspected
+    );
+    assert_eq!(htu.self_diagnose(&mut mock).unwrap(), Diagnosis::CrcFailure);
+
+    mock.done();
+}
+
+#[test]
+fn heater_self_test() {
+    let expected = [
+        Transaction::write(ADDRESS, vec![0xf3]),
+        Transaction::read(ADDRESS, vec![0x4e, 0x85, 0x6b]),
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0b0011_1010]),
+        Transaction::write(ADDRESS, vec![0xe6, 0b0011_1110]),
+        Transaction::write(ADDRESS, vec![0xf3]),
+        Transaction::read(ADDRESS, vec![0x4f, 0x84, 0xae]),
+        Transaction::write(ADDRESS, vec![0xe6, 0b0011_1010]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let rise = htu
+        .heater_self_test(&mut mock, &mut MockNoop::new())
+        .unwrap();
+    // 0x100 raw counts
+    assert!((rise - 0.686).abs() < 0.001);
+
+    mock.done();
+}
+
+#[test]
+fn temperature_oversampled() {
+    let expected = [
+        Transaction::write(ADDRESS, vec![0xf3]),
+        Transaction::read(ADDRESS, vec![0x4e, 0x85, 0x6b]),
+        Transaction::write(ADDRESS, vec![0xf3]),
+        // Off-scale, skipped
+        Transaction::read(ADDRESS, vec![0xff, 0xff, 0x2d]),
+        Transaction::write(ADDRESS, vec![0xf3]),
+        Transaction::read(ADDRESS, vec![0x4f, 0x84, 0xae]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let temperature = htu
+        .read_temperature_oversampled(&mut mock, &mut MockNoop::new(), 3)
+        .unwrap();
+    assert_eq!(temperature.as_raw(), 0x4f04);
+
+    mock.done();
+}
+
+#[test]
+fn effective_conversion_times() {
+    let expected = [Transaction::write_read(
+        ADDRESS,
+        vec![0xe7],
+        vec![0b1011_1010],
+    )];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    assert_eq!(htu.effective_conversion_times(&mut mock).unwrap(), (25, 5));
+
+    mock.done();
+}
+
+#[test]
+fn effective_conversion_times_otp_reload() {
+    // 10-bit humidity and 13-bit temperature resolution, but OTP reload is enabled
+    let expected = [Transaction::write_read(
+        ADDRESS,
+        vec![0xe7],
+        vec![0b1011_1000],
+    )];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    assert_eq!(htu.effective_conversion_times(&mut mock).unwrap(), (50, 16));
+
+    mock.done();
+}
+
+#[test]
+fn block_with_config() {
+    /// A ConnectionRefused error here represents a NAK
+    fn is_nak(error: &MockError) -> bool {
+        matches!(error, MockError::Io(io::ErrorKind::ConnectionRefused))
+    }
+
+    let config = PollConfig {
+        initial_delay_ms: 40,
+        max_attempts: 3,
+        backoff: 2,
+    };
+    let expected = [
+        Transaction::write(ADDRESS, vec![0xf3]),
+        Transaction::read(ADDRESS, vec![0u8; 3])
+            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
+        Transaction::read(ADDRESS, vec![0x4e, 0x85, 0x6b]),
+        // Never finishes
+        Transaction::write(ADDRESS, vec![0xf5]),
+        Transaction::read(ADDRESS, vec![0u8; 3])
+            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
+        Transaction::read(ADDRESS, vec![0u8; 3])
+            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
+        Transaction::read(ADDRESS, vec![0u8; 3])
+            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let temperature = htu
+        .read_temperature(&mut mock)
+        .unwrap()
+        .block_with_config(&mut mock, &mut MockNoop::new(), &config, is_nak)
+        .unwrap();
+    assert!(temperature.is_ok());
+    let humidity = htu.read_humidity(&mut mock).unwrap().block_with_config(
+        &mut mock,
+        &mut MockNoop::new(),
+        &config,
+        is_nak,
+    );
+    assert!(matches!(humidity, Err(htu2xd::Error::Timeout)));
+
+    mock.done();
+}
+
+#[test]
+fn detailed_blocking() {
+    let expected = [
+        Transaction::write_read(ADDRESS, vec![0xe3], vec![0x4e, 0x85, 0x6b]),
+        Transaction::write_read(ADDRESS, vec![0xe5], vec![0x68, 0x3a, 0x7c]),
+        Transaction::write_read(ADDRESS, vec![0xe5], vec![0xff, 0xff, 0x2d]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    match htu.read_temperature_detailed_blocking(&mut mock).unwrap() {
+        Reading::Ok((raw, degrees)) => {
+            assert_eq!(raw, 0x4e84);
+            assert!((degrees - 7.04).abs() < 0.01);
+        }
+        _ => panic!("Expected Ok"),
+    }
+    match htu.read_humidity_detailed_blocking(&mut mock).unwrap() {
+        Reading::Ok((raw, percent)) => {
+            assert_eq!(raw, 0x6838);
+            assert!((percent - 44.89).abs() < 0.01);
+        }
+        _ => panic!("Expected Ok"),
+    }
+    assert!(htu
+        .read_humidity_detailed_blocking(&mut mock)
+        .unwrap()
+        .is_error_high());
+
+    mock.done();
+}
+
+#[test]
+fn cached_temperature_support() {
+    let expected = [
+        Transaction::write(ADDRESS, vec![0xf5]),
+        Transaction::read(ADDRESS, vec![0x68, 0x3a, 0x7c]),
+        Transaction::write_read(ADDRESS, vec![0xe0], vec![0x4e, 0x85, 0x6b]),
+        // Not acknowledged
+        Transaction::write(ADDRESS, vec![0xf5]),
+        Transaction::read(ADDRESS, vec![0x68, 0x3a, 0x7c]),
+        Transaction::write_read(ADDRESS, vec![0xe0], vec![0u8; 3])
+            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
+        // Not a valid reading
+        Transaction::write(ADDRESS, vec![0xf5]),
+        Transaction::read(ADDRESS, vec![0x68, 0x3a, 0x7c]),
+        Transaction::write_read(ADDRESS, vec![0xe0], vec![0xff, 0xff, 0xff]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let mut delay = MockNoop::new();
+    assert!(htu
+        .supports_cached_temperature(&mut mock, &mut delay)
+        .unwrap());
+    assert!(!htu
+        .supports_cached_temperature(&mut mock, &mut delay)
+        .unwrap());
+    assert!(!htu
+        .supports_cached_temperature(&mut mock, &mut delay)
+        .unwrap());
+
+    mock.done();
+}
+
+#[test]
+fn start_and_finish() {
+    let expected = [
+        Transaction::write(ADDRESS, vec![0xf3]),
+        Transaction::read(ADDRESS, vec![0x4e, 0x85, 0x6b]),
+        Transaction::write(ADDRESS, vec![0xf5]),
+        Transaction::read(ADDRESS, vec![0x68, 0x3a, 0x7c]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let pending = htu
+        .start_temperature(&mut mock, &Resolution::Humidity12Temperature14)
+        .unwrap();
+    assert_eq!(pending.ready_after_ms(), 50);
+    assert!(htu.finish_temperature(pending, &mut mock).unwrap().is_ok());
+    let pending = htu
+        .start_humidity(&mut mock, &Resolution::Humidity8Temperature12)
+        .unwrap();
+    assert_eq!(pending.ready_after_ms(), 3);
+    assert!(htu.finish_humidity(pending, &mut mock).unwrap().is_ok());
+
+    mock.done();
+}
+
+#[test]
+fn climate_with_uncertainty() {
+    let expected = [
+        Transaction::write(ADDRESS, vec![0xf3]),
+        Transaction::read(ADDRESS, vec![0x4e, 0x85, 0x6b]),
+        Transaction::write(ADDRESS, vec![0xf5]),
+        Transaction::read(ADDRESS, vec![0x68, 0x3a, 0x7c]),
+        // Low supply voltage
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0b0111_1010]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let sample = htu
+        .read_climate_with_uncertainty(&mut mock, &mut MockNoop::new())
+        .unwrap();
+    assert_eq!(sample.temperature.as_raw(), 0x4e84);
+    assert_eq!(sample.humidity.as_raw(), 0x6838);
+    assert!(sample.temperature_uncertainty_c > 0.6);
+    assert!(sample.humidity_uncertainty_pct > sample.compensated_humidity_uncertainty_pct);
+
+    mock.done();
+}
+
+#[test]
+fn measurements_blocking() {
+    let expected = [
+        Transaction::write_read(ADDRESS, vec![0xe3], vec![0x4e, 0x85, 0x6b]),
+        Transaction::write_read(ADDRESS, vec![0xe5], vec![0x68, 0x3a, 0x7c]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    match htu.read_measurements_blocking(&mut mock).unwrap() {
+        (Reading::Ok(temperature), Reading::Ok(humidity)) => {
+            assert_eq!(temperature.as_raw(), 0x4e84);
+            assert_eq!(humidity.as_raw(), 0x6838);
+        }
+        other => panic!("Unexpected readings {:?}", other),
+    }
+
+    mock.done();
+}
+
+#[test]
+fn temperature_from_previous_humidity() {
+    let expected = [
+        Transaction::write_read(ADDRESS, vec![0xe5], vec![0x68, 0x3a, 0x7c]),
+        Transaction::write_read(ADDRESS, vec![0xe0], vec![0x4e, 0x85, 0x6b]),
+        // Corrupted CRC
+        Transaction::write_read(ADDRESS, vec![0xe0], vec![0x4e, 0x85, 0x6c]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    assert!(htu.read_humidity_blocking(&mut mock).unwrap().is_ok());
+    match htu
+        .read_temperature_from_previous_humidity(&mut mock)
+        .unwrap()
+    {
+        Reading::Ok(temperature) => assert_eq!(temperature.as_raw(), 0x4e84),
+        other => panic!("Unexpected reading {:?}", other),
+    }
+    let error = htu
+        .read_temperature_from_previous_humidity(&mut mock)
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        htu2xd::Error::Crc {
+            received: [_, _, 0x6c],
+            computed: 0x6b,
+        }
+    ));
+
+    mock.done();
+}
+
+#[test]
+fn probe() {
+    let expected = [
+        // Default register value
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0b0000_0010]),
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0b0011_1010]),
+        // No sensor
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0x00])
+            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
+        // Invalid reserved bits
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0b0001_0010]),
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0xff]),
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0x00]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    assert!(htu.probe(&mut mock).unwrap());
+    assert!(htu.probe(&mut mock).unwrap());
+    assert!(htu.probe(&mut mock).is_err());
+    assert!(!htu.probe(&mut mock).unwrap());
+    assert!(!htu.probe(&mut mock).unwrap());
+    assert!(!htu.probe(&mut mock).unwrap());
+
+    mock.done();
+}
+
+#[test]
+fn owned_bus() {
+    let expected = [
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0b0000_0010]),
+        Transaction::write_read(ADDRESS, vec![0xe3], vec![0x4e, 0x85, 0x6b]),
+        Transaction::write_read(ADDRESS, vec![0xe5], vec![0x68, 0x3a, 0x7c]),
+    ];
+    let mock = Mock::new(&expected);
+
+    let mut htu = Htu2xdOwned::new(mock);
+    assert!(htu.probe().unwrap());
+    assert!(htu.read_temperature_blocking().unwrap().is_ok());
+    assert!(htu.read_humidity_blocking().unwrap().is_ok());
+
+    let mut mock = htu.release();
+    mock.done();
+}
+
+#[test]
+fn custom_address() {
+    let expected = [
+        Transaction::write_read(0x41, vec![0xe3], vec![0x4e, 0x85, 0x6b]),
+        Transaction::write(0x41, vec![0xf5]),
+        Transaction::read(0x41, vec![0x68, 0x3a, 0x7c]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new_with_address(0x41);
+    assert_eq!(htu.address(), 0x41);
+    assert!(htu.read_temperature_blocking(&mut mock).unwrap().is_ok());
+    let reader = htu.read_humidity(&mut mock).unwrap();
+    match reader.read_result(&mut mock, |_| false).unwrap() {
+        ReadAttempt::Done(reading) => assert!(reading.is_ok()),
+        ReadAttempt::Pending(_) => panic!("Unexpected pending"),
+    }
+
+    mock.done();
+}
+
+#[test]
+fn custom_address_owned_and_tracking() {
+    let expected = [
+        Transaction::write_read(0x41, vec![0xe7], vec![0x02]),
+        Transaction::write(0x41, vec![0xf3]),
+        Transaction::read(0x41, vec![0x4e, 0x85, 0x6b]),
+    ];
+    let mock = Mock::new(&expected);
+
+    let mut owned = Htu2xdOwned::new_with_address(mock, 0x41);
+    assert_eq!(owned.address(), 0x41);
+    owned.read_user_register().unwrap();
+    let mut mock = owned.release();
+
+    let mut tracking = TrackingHtu2xd::new_with_address(0x41);
+    assert_eq!(tracking.address(), 0x41);
+    tracking
+        .start_measurement::<Temperature>(&mut mock)
+        .unwrap();
+    let reading = tracking.read_result::<Temperature, _>(&mut mock, |_| false);
+    assert!(reading.unwrap().is_ok());
+
+    mock.done();
+}
+
+#[test]
+fn heater() {
+    let expected = [
+        // Reserved bits set
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0b0011_1010]),
+        Transaction::write(ADDRESS, vec![0xe6, 0b0011_1110]),
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0b0011_1110]),
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0b0011_1110]),
+        Transaction::write(ADDRESS, vec![0xe6, 0b0011_1010]),
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0b0011_1010]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    htu.set_heater(&mut mock, true).unwrap();
+    assert!(htu.heater_enabled(&mut mock).unwrap());
+    htu.set_heater(&mut mock, false).unwrap();
+    assert!(!htu.heater_enabled(&mut mock).unwrap());
+
+    mock.done();
+}
+
+#[test]
+fn set_resolution() {
+    let expected = [
+        // Heater enabled
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0b0011_1110]),
+        Transaction::write(ADDRESS, vec![0xe6, 0b1011_1111]),
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0b1011_1111]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    htu.set_resolution(&mut mock, Resolution::Humidity11Temperature11)
+        .unwrap();
+    assert_eq!(
+        htu.get_resolution(&mut mock).unwrap(),
+        Resolution::Humidity11Temperature11
+    );
+
+    mock.done();
+}
+
+/// A mock I2C bus with an error type that implements `NakError`
+///
+/// `NakError` cannot be implemented for `MockError` directly because both are foreign to this
+/// crate, so this wraps the mock and its errors.
+struct NakMock(Mock);
+
+#[derive(Debug)]
+struct NakMockError(MockError);
+
+impl NakError for NakMockError {
+    fn is_nak(&self) -> bool {
+        matches!(self.0, MockError::Io(io::ErrorKind::ConnectionRefused))
+    }
+}
+
+impl Read for NakMock {
+    type Error = NakMockError;
+    fn read(&mut self, address: u8, buffer: &mut [u8]) -> Result<(), NakMockError> {
+        self.0.read(address, buffer).map_err(NakMockError)
+    }
+}
+
+impl Write for NakMock {
+    type Error = NakMockError;
+    fn write(&mut self, address: u8, bytes: &[u8]) -> Result<(), NakMockError> {
+        self.0.write(address, bytes).map_err(NakMockError)
+    }
+}
+
+impl WriteRead for NakMock {
+    type Error = NakMockError;
+    fn write_read(
+        &mut self,
+        address: u8,
+        bytes: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<(), NakMockError> {
+        self.0
+            .write_read(address, bytes, buffer)
+            .map_err(NakMockError)
+    }
+}
+
+/// Polls a measurement using the NakError implementation instead of a closure
+#[test]
+fn read_result_auto() {
+    let expected = [
+        Transaction::write(ADDRESS, vec![0xf3]),
+        Transaction::read(ADDRESS, vec![0u8; 3])
+            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
+        Transaction::read(ADDRESS, vec![0u8; 3])
+            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
+        Transaction::read(ADDRESS, vec![0x4e, 0x85, 0x6b]),
+    ];
+    let mut mock = NakMock(Mock::new(&expected));
+
+    let mut htu = Htu2xd::new();
+    let mut reader = htu.start_measurement::<Temperature>(&mut mock).unwrap();
+    let mut attempts = 0;
+    let reading = loop {
+        attempts += 1;
+        match reader.read_result_auto(&mut mock).unwrap() {
+            ReadAttempt::Pending(pending) => reader = pending,
+            ReadAttempt::Done(reading) => break reading,
+        }
+    };
+    assert_eq!(attempts, 3);
+    assert_eq!(reading.into_result().unwrap().as_raw(), 0x4e84);
+    mock.0.done();
+}
+
+#[test]
+fn result_reader_command_and_kind() {
+    let expected = [
+        Transaction::write(ADDRESS, vec![0xf3]),
+        Transaction::write(ADDRESS, vec![0xf5]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let temperature_step2 = htu.read_temperature(&mut mock).unwrap();
+    assert_eq!(temperature_step2.command(), 0xf3);
+    assert_eq!(temperature_step2.kind(), MeasurementType::Temperature);
+    let humidity_step2 = htu.read_humidity(&mut mock).unwrap();
+    assert_eq!(humidity_step2.command(), 0xf5);
+    assert_eq!(humidity_step2.kind(), MeasurementType::Humidity);
+
+    mock.done();
+}
+
+#[test]
+fn temperature_humidity_nak() {
+    /// A ConnectionRefused error here represents a NAK
+    fn is_nak(error: &MockError) -> bool {
+        matches!(error, MockError::Io(io::ErrorKind::ConnectionRefused))
+    }
+
+    let expected = [
+        // Start temperature read
+        Transaction::write(ADDRESS, vec![0xf3]),
+        // Several read attempts that return NAK
+        Transaction::read(ADDRESS, vec![0u8; 3])
+            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
+        Transaction::read(ADDRESS, vec![0u8; 3])
+            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
+        Transaction::read(ADDRESS, vec![0u8; 3])
+            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
+        Transaction::read(ADDRESS, vec![0u8; 3])
+            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
+        // Done measuring, return results
+        Transaction::read(ADDRESS, vec![0x4e, 0x85, 0x6b]),
+        // Start humidity read
+        Transaction::write(ADDRESS, vec![0xf5]),
+        // Several read attempts that return NAK
+        Transaction::read(ADDRESS, vec![0u8; 3])
+            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
+        Transaction::read(ADDRESS, vec![0u8; 3])
+            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
+        // Done measuring, return results
+        Transaction::read(ADDRESS, vec![0x68, 0x3a, 0x7c]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+
+    let mut temperature_step2 = htu.read_temperature(&mut mock).unwrap();
+    // 4 NAK errors while the sensor is measuring
+    for _ in 0..4 {
+        temperature_step2 = match temperature_step2.read_result(&mut mock, is_nak).unwrap() {
+            ReadAttempt::Pending(reader) => reader,
+            ReadAttempt::Done(_) => panic!("Unexpected result"),
+        };
+    }
+    // Now the sensor is done
+    match temperature_step2.read_result(&mut mock, is_nak).unwrap() {
+        ReadAttempt::Done(Reading::Ok(reading)) => {
+            assert_eq!(reading.as_raw(), 0x4e84);
+            let degrees_c = reading.as_degrees_celsius();
+            assert!(degrees_c >= 7.04);
+            assert!(degrees_c < 7.05);
+        }
+        ReadAttempt::Done(Reading::ErrorLow) => panic!("Unexpected error low"),
+        ReadAttempt::Done(Reading::ErrorHigh) => panic!("Unexpected error high"),
+        ReadAttempt::Pending(_) => panic!("Unexpected pending"),
+    }
+    let mut humidity_step2 = htu.read_humidity(&mut mock).unwrap();
+    // 2 NAK errors while the sensor is measuring
+    for _ in 0..2 {
+        humidity_step2 = match humidity_step2.read_result(&mut mock, is_nak).unwrap() {
+            ReadAttempt::Pending(reader) => reader,
+            ReadAttempt::Done(_) => panic!("Unexpected result"),
+        };
+    }
+    // Now the sensor is done
+    match humidity_step2.read_result(&mut mock, is_nak).unwrap() {
+        ReadAttempt::Done(Reading::Ok(reading)) => {
+            assert_eq!(reading.as_raw(), 0x6838);
+            let percent = reading.as_percent_relative();
+            assert!(percent >= 44.8);
+            assert!(percent < 44.9);
+        }
+        ReadAttempt::Done(Reading::ErrorLow) => panic!("Unexpected error low"),
+        ReadAttempt::Done(Reading::ErrorHigh) => panic!("Unexpected error high"),
+        ReadAttempt::Pending(_) => panic!("Unexpected pending"),
+    }
+
+    mock.done();
+}
+
+#[test]
+fn relative_humidity() {
+    let expected = [Transaction::write_read(
+        ADDRESS,
+        vec![0xe5],
+        vec![0x68, 0x3a, 0x7c],
+    )];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let humidity = htu.read_humidity_value_blocking(&mut mock).unwrap();
+    let relative = humidity.relative_humidity();
+    assert_eq!(relative.value(), humidity.as_percent_relative());
+    assert_eq!(relative.to_string(), "44.9 %");
+
+    mock.done();
+}
+
+#[test]
+fn reading_predicates() {
+    let expected = [Transaction::write_read(
+        ADDRESS,
+        vec![0xe3],
+        vec![0x4e, 0x85, 0x6b],
+    )];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let temperature = htu.read_temperature_blocking(&mut mock).unwrap();
+    assert!(temperature.is_ok());
+    assert!(!temperature.is_error_low());
+    assert!(!temperature.is_error_high());
+    let low: Reading<Temperature> = Reading::ErrorLow;
+    assert!(!low.is_ok());
+    assert!(low.is_error_low());
+    assert!(!low.is_error_high());
+    let high: Reading<Temperature> = Reading::ErrorHigh;
+    assert!(!high.is_ok());
+    assert!(!high.is_error_low());
+    assert!(high.is_error_high());
+
+    mock.done();
+}
+
+#[test]
+fn generic_start_measurement() {
+    /// A ConnectionRefused error here represents a NAK
+    fn is_nak(error: &MockError) -> bool {
+        matches!(error, MockError::Io(io::ErrorKind::ConnectionRefused))
+    }
+    /// Measures either quantity
+    fn measure<M: Measurement>(htu: &mut Htu2xd<Mock>, mock: &mut Mock) -> Reading<M> {
+        let mut reader = htu.start_measurement::<M>(mock).unwrap();
+        loop {
+            match reader.read_result(mock, is_nak).unwrap() {
+                ReadAttempt::Pending(pending) => reader = pending,
+                ReadAttempt::Done(reading) => break reading,
+            }
+        }
+    }
+
+    let expected = [
+        Transaction::write(ADDRESS, vec![0xf3]),
+        Transaction::read(ADDRESS, vec![0x4e, 0x85, 0x6b]),
+        Transaction::write(ADDRESS, vec![0xf5]),
+        Transaction::read(ADDRESS, vec![0x68, 0x3a, 0x7c]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    match measure::<Temperature>(&mut htu, &mut mock) {
+        Reading::Ok(reading) => assert_eq!(reading.as_raw(), 0x4e84),
+        _ => panic!("Unexpected error"),
+    }
+    match measure::<Humidity>(&mut htu, &mut mock) {
+        Reading::Ok(reading) => assert_eq!(reading.as_raw(), 0x6838),
+        _ => panic!("Unexpected error"),
+    }
+
+    mock.done();
+}
+
+#[test]
+fn humidity_compensated_nak() {
+    /// A ConnectionRefused error here represents a NAK
+    fn is_nak(error: &MockError) -> bool {
+        matches!(error, MockError::Io(io::ErrorKind::ConnectionRefused))
+    }
+
+    let expected = [
+        Transaction::write_read(ADDRESS, vec![0xe3], vec![0x4e, 0x85, 0x6b]),
+        Transaction::write(ADDRESS, vec![0xf5]),
+        Transaction::read(ADDRESS, vec![0u8; 3])
+            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
+        Transaction::read(ADDRESS, vec![0x68, 0x3a, 0x7c]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let temperature = htu.read_temperature_value_blocking(&mut mock).unwrap();
+    let humidity_step2 = htu.read_humidity(&mut mock).unwrap();
+    let humidity_step2 = match humidity_step2
+        .read_result_compensated(&mut mock, &temperature, is_nak)
+        .unwrap()
+    {
+        ReadAttempt::Pending(reader) => reader,
+        ReadAttempt::Done(_) => panic!("Unexpected result"),
+    };
+    match humidity_step2
+        .read_result_compensated(&mut mock, &temperature, is_nak)
+        .unwrap()
+    {
+        ReadAttempt::Done(Reading::Ok(percent)) => {
+            // 44.8% at 7.04 degrees C, compensated by -0.15 %RH/°C
+            assert!(percent >= 42.1);
+            assert!(percent < 42.2);
+        }
+        ReadAttempt::Done(Reading::ErrorLow) => panic!("Unexpected error low"),
+        ReadAttempt::Done(Reading::ErrorHigh) => panic!("Unexpected error high"),
+        ReadAttempt::Pending(_) => panic!("Unexpected pending"),
+    }
+
+    mock.done();
+}
+
+#[test]
+fn temperature_poll_iter() {
+    /// A ConnectionRefused error here represents a NAK
+    fn is_nak(error: &MockError) -> bool {
+        matches!(error, MockError::Io(io::ErrorKind::ConnectionRefused))
+    }
+
+    let expected = [
+        Transaction::write(ADDRESS, vec![0xf3]),
+        Transaction::read(ADDRESS, vec![0u8; 3])
+            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
+        Transaction::read(ADDRESS, vec![0u8; 3])
+            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
+        Transaction::read(ADDRESS, vec![0x4e, 0x85, 0x6b]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let reader = htu.read_temperature(&mut mock).unwrap();
+    let mut polls = reader.into_poll_iter(&mut mock, is_nak);
+    assert!(matches!(polls.next(), Some(Poll::Pending)));
+    assert!(matches!(polls.next(), Some(Poll::Pending)));
+    match polls.next() {
+        Some(Poll::Ready(Ok(Reading::Ok(reading)))) => assert_eq!(reading.as_raw(), 0x4e84),
+        _ => panic!("Expected a reading"),
+    }
+    assert!(polls.next().is_none());
+
+    mock.done();
+}
+
+#[test]
+fn tracking_pending_measurement() {
+    /// A ConnectionRefused error here represents a NAK
+    fn is_nak(error: &MockError) -> bool {
+        matches!(error, MockError::Io(io::ErrorKind::ConnectionRefused))
+    }
+
+    let expected = [
+        Transaction::write(ADDRESS, vec![0xf3]),
+        Transaction::read(ADDRESS, vec![0u8; 3])
+            .with_error(MockError::Io(io::ErrorKind::ConnectionRefused)),
+        Transaction::read(ADDRESS, vec![0x4e, 0x85, 0x6b]),
+        Transaction::write(ADDRESS, vec![0xf5]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = TrackingHtu2xd::new();
+    assert!(!htu.has_pending_measurement());
+    htu.start_measurement::<Temperature>(&mut mock).unwrap();
+    assert!(htu.has_pending_measurement());
+    // Can't start another measurement
+    let error = htu.start_measurement::<Humidity>(&mut mock).unwrap_err();
+    assert!(matches!(error, htu2xd::Error::MeasurementInProgress));
+    // Can't read the wrong type of result
+    let error = htu
+        .read_result::<Humidity, _>(&mut mock, is_nak)
+        .unwrap_err();
+    assert!(matches!(
+        error,
+        nb::Error::Other(htu2xd::Error::NoMeasurementPending)
+    ));
+
+    let error = htu
+        .read_result::<Temperature, _>(&mut mock, is_nak)
+        .unwrap_err();
+    assert!(matches!(error, nb::Error::WouldBlock));
+    assert!(htu.has_pending_measurement());
+    let reading = htu
+        .read_result::<Temperature, _>(&mut mock, is_nak)
+        .unwrap();
+    assert!(reading.is_ok());
+    assert!(!htu.has_pending_measurement());
+
+    // Now another measurement can start
+    htu.start_measurement::<Humidity>(&mut mock).unwrap();
+
+    mock.done();
+}
+
+#[test]
+fn parse_with_reused_crc() {
+    let mut crc = Crc::new();
+    let temperature: Reading<Temperature> =
+        parse_reading_with_crc(&[0x4e, 0x85, 0x6b], &mut crc).unwrap();
+    assert!(temperature.is_ok());
+    let humidity: Reading<Humidity> =
+        parse_reading_with_crc(&[0x68, 0x3a, 0x7c], &mut crc).unwrap();
+    assert!(humidity.is_ok());
+    let corrupted = parse_reading_with_crc::<Humidity>(&[0x68, 0x3b, 0x7c], &mut crc);
+    assert_eq!(corrupted.unwrap_err(), CrcError::Mismatch);
+}
+
+#[test]
+fn reading_try_from_slice() {
+    let temperature = Reading::<Temperature>::try_from(&[0x4e, 0x85, 0x6b][..]).unwrap();
+    assert!(temperature.is_ok());
+    assert_eq!(
+        Reading::<Humidity>::try_from(&[0x68, 0x3a][..]).unwrap_err(),
+        FrameError::Length(2)
+    );
+    assert_eq!(
+        Reading::<Humidity>::try_from(&[0x68, 0x3a, 0x7c, 0x00][..]).unwrap_err(),
+        FrameError::Length(4)
+    );
+    assert_eq!(
+        Reading::<Humidity>::try_from(&[0x68, 0x3b, 0x7c][..]).unwrap_err(),
+        FrameError::Crc
+    );
+}
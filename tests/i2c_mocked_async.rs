@@ -0,0 +1,197 @@
+#![cfg(feature = "async")]
+
+extern crate embedded_hal_mock_1 as embedded_hal_mock;
+extern crate futures_util;
+extern crate htu2xd;
+
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+use embedded_hal_mock::eh1::delay::{CheckedDelay, Transaction as DelayTransaction};
+use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+use futures_util::StreamExt;
+use htu2xd::hal_async::Htu2xdAsync;
+use htu2xd::Resolution;
+
+/// Address of the sensor
+const ADDRESS: u8 = 0x40;
+
+/// Runs a future that never waits for anything
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut context = Context::from_waker(Waker::noop());
+    match future.as_mut().poll(&mut context) {
+        Poll::Ready(output) => output,
+        Poll::Pending => panic!("Future not ready"),
+    }
+}
+
+/// Takes three samples from the stream, one second apart
+#[test]
+fn stream() {
+    let sample = [
+        Transaction::write(ADDRESS, vec![0xf3]),
+        Transaction::read(ADDRESS, vec![0x4e, 0x85, 0x6b]),
+        Transaction::write(ADDRESS, vec![0xf5]),
+        Transaction::read(ADDRESS, vec![0x68, 0x3a, 0x7c]),
+    ];
+    let expected: Vec<Transaction> = sample.iter().cycle().take(12).cloned().collect();
+    let mut mock = Mock::new(&expected);
+    // The conversion times (50 ms and 16 ms) are subtracted from the interval
+    let mut delay = CheckedDelay::new(&[
+        DelayTransaction::async_delay_ms(50),
+        DelayTransaction::async_delay_ms(16),
+        DelayTransaction::async_delay_ms(934),
+        DelayTransaction::async_delay_ms(50),
+        DelayTransaction::async_delay_ms(16),
+        DelayTransaction::async_delay_ms(934),
+        DelayTransaction::async_delay_ms(50),
+        DelayTransaction::async_delay_ms(16),
+    ]);
+    let mut htu = Htu2xdAsync::new();
+    {
+        let mut samples = pin!(htu.stream(&mut mock, &mut delay, 1000));
+        for _ in 0..3 {
+            let (temperature, humidity) = block_on(samples.next()).unwrap().unwrap();
+            assert_eq!(temperature.as_raw(), 0x4e84);
+            assert_eq!(humidity.as_raw(), 0x6838);
+        }
+    }
+    mock.done();
+    delay.done();
+}
+
+/// Takes samples one after another when the interval is shorter than the conversion times
+#[test]
+fn stream_short_interval() {
+    let sample = [
+        Transaction::write(ADDRESS, vec![0xf3]),
+        Transaction::read(ADDRESS, vec![0x4e, 0x85, 0x6b]),
+        Transaction::write(ADDRESS, vec![0xf5]),
+        Transaction::read(ADDRESS, vec![0x68, 0x3a, 0x7c]),
+    ];
+    let expected: Vec<Transaction> = sample.iter().cycle().take(8).cloned().collect();
+    let mut mock = Mock::new(&expected);
+    let mut delay = CheckedDelay::new(&[
+        DelayTransaction::async_delay_ms(50),
+        DelayTransaction::async_delay_ms(16),
+        DelayTransaction::async_delay_ms(0),
+        DelayTransaction::async_delay_ms(50),
+        DelayTransaction::async_delay_ms(16),
+    ]);
+    let mut htu = Htu2xdAsync::new();
+    {
+        let mut samples = pin!(htu.stream(&mut mock, &mut delay, 10));
+        for _ in 0..2 {
+            assert!(block_on(samples.next()).unwrap().is_ok());
+        }
+    }
+    mock.done();
+    delay.done();
+}
+
+/// Measures with the default resolution, changes the resolution, and checks that the
+/// conversion delays follow it
+#[test]
+fn measure_with_resolution() {
+    let expected = [
+        Transaction::write(ADDRESS, vec![0xf3]),
+        Transaction::read(ADDRESS, vec![0x4e, 0x85, 0x6b]),
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0x02]),
+        Transaction::write(ADDRESS, vec![0xe6, 0x03]),
+        Transaction::write(ADDRESS, vec![0xf5]),
+        Transaction::read(ADDRESS, vec![0x68, 0x3a, 0x7c]),
+        Transaction::write(ADDRESS, vec![0xfe]),
+    ];
+    let mut mock = Mock::new(&expected);
+    let mut delay = CheckedDelay::new(&[
+        // Temperature conversion at 14 bits
+        DelayTransaction::async_delay_ms(50),
+        // Humidity conversion at 8 bits
+        DelayTransaction::async_delay_ms(3),
+        // Soft reset
+        DelayTransaction::async_delay_ms(15),
+    ]);
+    let mut htu = Htu2xdAsync::new();
+
+    let temperature = block_on(htu.read_temperature(&mut mock, &mut delay)).unwrap();
+    assert_eq!(temperature.into_result().unwrap().as_raw(), 0x4e84);
+
+    let register = block_on(htu.read_user_register(&mut mock)).unwrap();
+    let register = register.with_resolution(Resolution::Humidity8Temperature12);
+    block_on(htu.write_user_register(&mut mock, register)).unwrap();
+
+    let humidity = block_on(htu.read_humidity(&mut mock, &mut delay)).unwrap();
+    assert_eq!(humidity.into_result().unwrap().as_raw(), 0x6838);
+
+    block_on(htu.soft_reset(&mut mock, &mut delay)).unwrap();
+    mock.done();
+    delay.done();
+}
+
+#[test]
+fn custom_address() {
+    let expected = [
+        Transaction::write(0x41, vec![0xf5]),
+        Transaction::read(0x41, vec![0x68, 0x3a, 0x7c]),
+        Transaction::write_read(0x41, vec![0xe7], vec![0x02]),
+    ];
+    let mut mock = Mock::new(&expected);
+    let mut delay = CheckedDelay::new(&[DelayTransaction::async_delay_ms(16)]);
+
+    let mut htu = Htu2xdAsync::new_with_address(0x41);
+    assert_eq!(htu.address(), 0x41);
+    let humidity = block_on(htu.read_humidity(&mut mock, &mut delay)).unwrap();
+    assert!(humidity.is_ok());
+    block_on(htu.read_user_register(&mut mock)).unwrap();
+    mock.done();
+    delay.done();
+}
+
+/// Checks that OTP reload is disabled so that the sensor keeps the configured resolution
+#[test]
+fn write_disables_otp_reload() {
+    let expected = [
+        // OTP reload enabled (bit 1 clear)
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0x00]),
+        // 8-bit humidity, OTP reload disabled
+        Transaction::write(ADDRESS, vec![0xe6, 0x03]),
+        Transaction::write(ADDRESS, vec![0xf5]),
+        Transaction::read(ADDRESS, vec![0x68, 0x3a, 0x7c]),
+    ];
+    let mut mock = Mock::new(&expected);
+    let mut delay = CheckedDelay::new(&[DelayTransaction::async_delay_ms(3)]);
+    let mut htu = Htu2xdAsync::new();
+
+    let register = block_on(htu.read_user_register(&mut mock)).unwrap();
+    assert!(register.otp_reload_enabled());
+    let register = register.with_resolution(Resolution::Humidity8Temperature12);
+    block_on(htu.write_user_register(&mut mock, register)).unwrap();
+    let humidity = block_on(htu.read_humidity(&mut mock, &mut delay)).unwrap();
+    assert!(humidity.is_ok());
+
+    mock.done();
+    delay.done();
+}
+
+/// Checks that the driver waits for the default conversion times if OTP reload is enabled
+#[test]
+fn read_with_otp_reload() {
+    let expected = [
+        // 8-bit humidity, OTP reload enabled
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0x01]),
+        Transaction::write(ADDRESS, vec![0xf5]),
+        Transaction::read(ADDRESS, vec![0x68, 0x3a, 0x7c]),
+    ];
+    let mut mock = Mock::new(&expected);
+    let mut delay = CheckedDelay::new(&[DelayTransaction::async_delay_ms(16)]);
+    let mut htu = Htu2xdAsync::new();
+
+    block_on(htu.read_user_register(&mut mock)).unwrap();
+    let humidity = block_on(htu.read_humidity(&mut mock, &mut delay)).unwrap();
+    assert!(humidity.is_ok());
+
+    mock.done();
+    delay.done();
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "hal-1")]
+
+extern crate embedded_hal_mock_1 as embedded_hal_mock;
+extern crate htu2xd;
+
+use embedded_hal_mock::eh1::i2c::{Mock, Transaction};
+use htu2xd::hal_1::Htu2xd;
+use htu2xd::Resolution;
+
+/// Address of the sensor
+const ADDRESS: u8 = 0x40;
+
+/// Reads both quantities, reads and writes the user register, and resets the sensor
+#[test]
+fn read_and_configure() {
+    let expected = [
+        Transaction::write_read(ADDRESS, vec![0xe3], vec![0x4e, 0x85, 0x6b]),
+        Transaction::write_read(ADDRESS, vec![0xe5], vec![0x68, 0x3a, 0x7c]),
+        Transaction::write_read(ADDRESS, vec![0xe7], vec![0x02]),
+        Transaction::write(ADDRESS, vec![0xe6, 0x83]),
+        Transaction::write(ADDRESS, vec![0xfe]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new();
+    let temperature = htu.read_temperature_blocking(&mut mock).unwrap();
+    assert_eq!(temperature.into_result().unwrap().as_raw(), 0x4e84);
+    let humidity = htu.read_humidity_blocking(&mut mock).unwrap();
+    assert_eq!(humidity.into_result().unwrap().as_raw(), 0x6838);
+
+    let register = htu.read_user_register(&mut mock).unwrap();
+    assert_eq!(register.resolution(), Resolution::Humidity12Temperature14);
+    let register = register.with_resolution(Resolution::Humidity11Temperature11);
+    htu.write_user_register(&mut mock, register).unwrap();
+
+    htu.soft_reset(&mut mock).unwrap();
+    mock.done();
+}
+
+#[test]
+fn custom_address() {
+    let expected = [
+        Transaction::write_read(0x41, vec![0xe3], vec![0x4e, 0x85, 0x6b]),
+        Transaction::write(0x41, vec![0xfe]),
+    ];
+    let mut mock = Mock::new(&expected);
+
+    let mut htu = Htu2xd::new_with_address(0x41);
+    assert_eq!(htu.address(), 0x41);
+    assert!(htu.read_temperature_blocking(&mut mock).unwrap().is_ok());
+    htu.soft_reset(&mut mock).unwrap();
+    mock.done();
+}
@@ -0,0 +1,24 @@
+#![cfg(feature = "test-utils")]
+
+extern crate htu2xd;
+
+use htu2xd::test_utils::build_reading_frame;
+use htu2xd::MeasurementType;
+
+#[test]
+fn humidity_frame() {
+    assert_eq!(
+        build_reading_frame(0x6838, MeasurementType::Humidity),
+        [0x68, 0x3a, 0x7c]
+    );
+}
+
+#[test]
+fn temperature_frame() {
+    let frame = build_reading_frame(0x4e84, MeasurementType::Temperature);
+    assert_eq!(frame[..2], [0x4e, 0x84]);
+
+    let mut crc = htu2xd::Crc::new();
+    crc.add_all(&frame);
+    assert_eq!(crc.value(), 0);
+}